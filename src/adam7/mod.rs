@@ -0,0 +1,175 @@
+//! Adam7 interlacing. The image is stored as seven reduced images, each
+//! taking every nth pixel of every nth row from a different starting
+//! point, so that a coarse version of the whole image can be shown after
+//! the first few passes. Each pass is filtered as an image of its own and
+//! passes with no pixels take up no bytes at all. See
+//! [Interlaced data order](http://www.libpng.org/pub/png/spec/1.2/PNG-DataRep.html#DR.Interlaced-data-order).
+
+#[cfg(test)]
+mod unit_tests;
+
+use std::fmt::Display;
+
+use crate::{error::PngError, filter, image_header::ImageHeader};
+
+/// The pixels of the full image that make up one pass.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Pass {
+    pub x_start: u32,
+    pub y_start: u32,
+    pub x_step: u32,
+    pub y_step: u32,
+}
+
+/// The seven passes, in the order they are stored
+pub const PASSES: [Pass; 7] = [
+    Pass::new(0, 0, 8, 8),
+    Pass::new(4, 0, 8, 8),
+    Pass::new(0, 4, 4, 8),
+    Pass::new(2, 0, 4, 4),
+    Pass::new(0, 2, 2, 4),
+    Pass::new(1, 0, 2, 2),
+    Pass::new(0, 1, 1, 2),
+];
+
+impl Pass {
+    const fn new(x_start: u32, y_start: u32, x_step: u32, y_step: u32) -> Pass {
+        Pass {
+            x_start,
+            y_start,
+            x_step,
+            y_step,
+        }
+    }
+    /// Width and height of the reduced image this pass makes of a `width`
+    /// by `height` image
+    pub fn size(&self, width: u32, height: u32) -> (u32, u32) {
+        (
+            width.saturating_sub(self.x_start).div_ceil(self.x_step),
+            height.saturating_sub(self.y_start).div_ceil(self.y_step),
+        )
+    }
+}
+
+/// How big one pass of an image is.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct PassSize {
+    pub width: u32,
+    pub height: u32,
+    /// Bytes of inflated image data, filter type bytes included
+    pub data_length: u64,
+}
+
+impl Display for PassSize {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}x{}, {} bytes",
+            self.width, self.height, self.data_length
+        )
+    }
+}
+
+/// The size of each of the seven passes of an image with this header.
+/// Fails if a pass has more data than fits in a u64.
+pub fn pass_sizes(header: &ImageHeader) -> Result<[PassSize; 7], PngError> {
+    let mut sizes = [PassSize {
+        width: 0,
+        height: 0,
+        data_length: 0,
+    }; 7];
+    for (pass, size) in PASSES.iter().zip(&mut sizes) {
+        let (width, height) = pass.size(header.width, header.height);
+        let data_length = match width == 0 || height == 0 {
+            true => 0,
+            false => header.scanlines_length(width, height)?,
+        };
+        *size = PassSize {
+            width,
+            height,
+            data_length,
+        };
+    }
+    Ok(sizes)
+}
+
+/// Unfilter the seven passes in `data` and put their pixels back in place,
+/// giving the unfiltered scanlines of the full image
+pub fn deinterlace(data: &[u8], header: &ImageHeader) -> Result<Vec<u8>, PngError> {
+    let expected = header.data_length()?;
+    if data.len() as u64 != expected {
+        return Err(PngError::InvalidImage {
+            reason: format!("image data is {} bytes, expected {expected}", data.len()),
+            offset: 0,
+            index: 0,
+        });
+    }
+    let bits = header.bits_per_pixel();
+    let row_bytes = header.row_bytes(header.width);
+    let mut raw = vec![0; row_bytes * header.height as usize];
+    let mut rest = data;
+    for (pass, size) in PASSES.iter().zip(pass_sizes(header)?) {
+        if size.data_length == 0 {
+            continue;
+        }
+        let (pass_data, next) = rest.split_at(size.data_length as usize);
+        rest = next;
+        let pass_row_bytes = header.row_bytes(size.width);
+        let pixels = filter::unfilter(
+            pass_data,
+            pass_row_bytes,
+            size.height as usize,
+            bits.div_ceil(8),
+        )?;
+        for (y, row) in pixels.chunks_exact(pass_row_bytes).enumerate() {
+            let full_y = (pass.y_start + y as u32 * pass.y_step) as usize;
+            let full_row = &mut raw[full_y * row_bytes..][..row_bytes];
+            for x in 0..size.width {
+                let full_x = pass.x_start + x * pass.x_step;
+                copy_pixel(row, x as usize, full_row, full_x as usize, bits);
+            }
+        }
+    }
+    Ok(raw)
+}
+
+/// Split the unfiltered scanlines of a full image into the unfiltered
+/// scanlines of each of the seven passes. Empty passes give empty vectors.
+pub fn interlace(raw: &[u8], header: &ImageHeader) -> Vec<Vec<u8>> {
+    let bits = header.bits_per_pixel();
+    let row_bytes = header.row_bytes(header.width);
+    PASSES
+        .iter()
+        .zip(pass_sizes(header).expect("passes of scanlines in memory fit in a u64"))
+        .map(|(pass, size)| {
+            let pass_row_bytes = header.row_bytes(size.width);
+            let mut pixels = vec![0; pass_row_bytes * size.height as usize];
+            for (y, row) in pixels.chunks_exact_mut(pass_row_bytes.max(1)).enumerate() {
+                let full_y = (pass.y_start + y as u32 * pass.y_step) as usize;
+                let full_row = &raw[full_y * row_bytes..][..row_bytes];
+                for x in 0..size.width {
+                    let full_x = pass.x_start + x * pass.x_step;
+                    copy_pixel(full_row, full_x as usize, row, x as usize, bits);
+                }
+            }
+            pixels
+        })
+        .collect()
+}
+
+/// Copy the `bits` bit pixel at `from_x` in `from` to `to_x` in `to`.
+/// Pixels smaller than a byte are packed from the most significant bit
+/// down.
+fn copy_pixel(from: &[u8], from_x: usize, to: &mut [u8], to_x: usize, bits: usize) {
+    if bits >= 8 {
+        let bytes = bits / 8;
+        to[to_x * bytes..][..bytes].copy_from_slice(&from[from_x * bytes..][..bytes]);
+        return;
+    }
+    let mask = (1u8 << bits) - 1;
+    let from_shift = 8 - bits - from_x * bits % 8;
+    let value = (from[from_x * bits / 8] >> from_shift) & mask;
+    let to_shift = 8 - bits - to_x * bits % 8;
+    let byte = &mut to[to_x * bits / 8];
+    *byte = (*byte & !(mask << to_shift)) | (value << to_shift);
+}
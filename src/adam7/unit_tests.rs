@@ -0,0 +1,122 @@
+use super::*;
+use crate::{
+    encoder::encode_scanlines,
+    filter::FilterStrategy,
+    image_header::{ColorType, InterlaceMethod},
+    test_utils,
+};
+
+fn header(width: u32, height: u32, bit_depth: u8, color_type: ColorType) -> ImageHeader {
+    ImageHeader {
+        interlace_method: InterlaceMethod::Adam7,
+        ..test_utils::header(width, height, bit_depth, color_type)
+    }
+}
+
+/// Scanlines of made up pixels, with any padding bits at the end of each
+/// row left as zero
+fn testing_scanlines(header: &ImageHeader) -> Vec<u8> {
+    let row_bytes = header.row_bytes(header.width);
+    let padding = row_bytes * 8 - header.width as usize * header.bits_per_pixel();
+    let mut raw = Vec::new();
+    for y in 0..header.height as usize {
+        let mut row: Vec<u8> = (0..row_bytes).map(|x| (x * 7 + y * 13) as u8).collect();
+        if let Some(last) = row.last_mut() {
+            *last &= 0xff << padding;
+        }
+        raw.extend(row);
+    }
+    raw
+}
+
+#[test]
+fn test_pass_sizes() {
+    let sizes: Vec<(u32, u32)> = pass_sizes(&header(8, 8, 8, ColorType::Grayscale))
+        .unwrap()
+        .iter()
+        .map(|size| (size.width, size.height))
+        .collect();
+    assert_eq!(
+        sizes,
+        [(1, 1), (1, 1), (2, 1), (2, 2), (4, 2), (4, 4), (8, 4)]
+    );
+
+    let sizes = pass_sizes(&header(1, 1, 8, ColorType::Rgb)).unwrap();
+    assert_eq!(sizes[0].data_length, 4);
+    assert!(sizes[1..].iter().all(|size| size.data_length == 0));
+}
+
+#[test]
+fn test_pass_data_length() {
+    let header = header(10, 3, 1, ColorType::Grayscale);
+    let sizes = pass_sizes(&header).unwrap();
+    assert_eq!(sizes[2].to_string(), "3x0, 0 bytes");
+    assert_eq!(sizes[6].to_string(), "10x1, 3 bytes");
+    assert_eq!(sizes[5].to_string(), "5x2, 4 bytes");
+    assert_eq!(
+        header.data_length().unwrap(),
+        sizes.iter().map(|size| size.data_length).sum::<u64>()
+    );
+}
+
+#[test]
+fn test_maximal_header() {
+    // every pass of the largest valid header fits, but not all of them
+    let max = u32::MAX >> 1;
+    let header = header(max, max, 16, ColorType::Rgba);
+    assert!(pass_sizes(&header).is_ok());
+    assert!(matches!(
+        deinterlace(&[0], &header),
+        Err(PngError::InvalidHeader { .. })
+    ));
+    let header = ImageHeader {
+        width: u32::MAX,
+        height: u32::MAX,
+        ..header
+    };
+    assert!(matches!(
+        pass_sizes(&header),
+        Err(PngError::InvalidHeader { .. })
+    ));
+}
+
+#[test]
+fn test_round_trip() {
+    for header in [
+        header(13, 11, 8, ColorType::Rgb),
+        header(9, 17, 16, ColorType::Rgba),
+        header(16, 5, 1, ColorType::Grayscale),
+        header(5, 9, 2, ColorType::Indexed),
+        header(3, 2, 4, ColorType::Grayscale),
+        header(1, 1, 8, ColorType::GrayscaleAlpha),
+    ] {
+        let raw = testing_scanlines(&header);
+        let data = encode_scanlines(&raw, &header, FilterStrategy::Adaptive);
+        assert_eq!(data.len() as u64, header.data_length().unwrap(), "{header}");
+        assert_eq!(deinterlace(&data, &header).unwrap(), raw, "{header}");
+    }
+}
+
+#[test]
+fn test_first_pass_is_top_left_of_each_block() {
+    let header = header(16, 16, 8, ColorType::Grayscale);
+    let raw: Vec<u8> = (0..=255).collect();
+    let passes = interlace(&raw, &header);
+    assert_eq!(passes[0], [0, 8, 128, 136]);
+    assert_eq!(passes[1], [4, 12, 132, 140]);
+    assert_eq!(passes[6].len(), 16 * 8);
+}
+
+#[test]
+fn test_wrong_length() {
+    let header = header(4, 4, 8, ColorType::Grayscale);
+    let data = encode_scanlines(
+        &testing_scanlines(&header),
+        &header,
+        FilterStrategy::Adaptive,
+    );
+    assert!(matches!(
+        deinterlace(&data[1..], &header),
+        Err(PngError::InvalidImage { .. })
+    ));
+}
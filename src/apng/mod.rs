@@ -0,0 +1,453 @@
+//! Animated PNG. An acTL chunk before the image data says how many frames
+//! there are and how often to play them. Each frame starts with an fcTL
+//! chunk giving the region of the canvas it covers, how long it is shown
+//! and how it is combined with what is already there. The first frame's
+//! pixels may be the IDAT image itself; the rest are stored in fdAT chunks,
+//! which are IDAT data prefixed with a sequence number. fcTL and fdAT chunks
+//! share one sequence, starting at 0, so that reordering can be detected.
+//! See [APNG Specification](https://wiki.mozilla.org/APNG_Specification).
+
+#[cfg(test)]
+mod unit_tests;
+
+use std::{fmt::Display, time::Duration};
+
+use crate::{
+    chunk::Chunk,
+    chunk_type::ChunkType,
+    error::PngError,
+    image::{self, Image},
+    image_header::ImageHeader,
+    png::Png,
+};
+
+/// The contents of an acTL chunk.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct AnimationControl {
+    pub num_frames: u32,
+    /// How many times to play the animation, 0 meaning forever
+    pub num_plays: u32,
+}
+
+impl AnimationControl {
+    /// Length of the acTL chunk data
+    pub const LENGTH: usize = 8;
+
+    /// The 8 bytes of acTL chunk data
+    pub fn as_bytes(&self) -> [u8; AnimationControl::LENGTH] {
+        let mut bytes = [0; AnimationControl::LENGTH];
+        bytes[0..4].copy_from_slice(&self.num_frames.to_be_bytes());
+        bytes[4..8].copy_from_slice(&self.num_plays.to_be_bytes());
+        bytes
+    }
+    /// Build the acTL chunk holding this control
+    pub fn to_chunk(&self) -> Chunk {
+        Chunk::new(ChunkType::acTL, self.as_bytes().to_vec())
+    }
+}
+
+impl TryFrom<&[u8]> for AnimationControl {
+    type Error = PngError;
+
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        if value.len() != AnimationControl::LENGTH {
+            return Err(invalid(format!(
+                "acTL is {} bytes, expected {}",
+                value.len(),
+                AnimationControl::LENGTH
+            )));
+        }
+        Ok(AnimationControl {
+            num_frames: word(value, 0),
+            num_plays: word(value, 4),
+        })
+    }
+}
+
+impl Display for AnimationControl {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.num_plays {
+            0 => write!(f, "{} frames, plays forever", self.num_frames),
+            plays => write!(f, "{} frames, plays {plays} times", self.num_frames),
+        }
+    }
+}
+
+/// What happens to a frame's region of the canvas once it has been shown.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DisposeOp {
+    /// Leave the canvas as it is
+    None = 0,
+    /// Clear the region to fully transparent black
+    Background = 1,
+    /// Put the region back to how it was before the frame
+    Previous = 2,
+}
+
+/// How a frame is combined with the canvas.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum BlendOp {
+    /// Replace the region, alpha included
+    Source = 0,
+    /// Draw the frame over the region using its alpha
+    Over = 1,
+}
+
+impl Display for DisposeOp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            DisposeOp::None => "none",
+            DisposeOp::Background => "background",
+            DisposeOp::Previous => "previous",
+        };
+        write!(f, "{name}")
+    }
+}
+
+impl Display for BlendOp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            BlendOp::Source => "source",
+            BlendOp::Over => "over",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// The contents of an fcTL chunk.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct FrameControl {
+    pub sequence_number: u32,
+    pub width: u32,
+    pub height: u32,
+    pub x_offset: u32,
+    pub y_offset: u32,
+    /// Numerator of the delay in seconds
+    pub delay_num: u16,
+    /// Denominator of the delay in seconds, 0 meaning 100
+    pub delay_den: u16,
+    pub dispose_op: DisposeOp,
+    pub blend_op: BlendOp,
+}
+
+impl FrameControl {
+    /// Length of the fcTL chunk data
+    pub const LENGTH: usize = 26;
+
+    /// How long the frame is shown for
+    pub fn delay(&self) -> Duration {
+        let den = match self.delay_den {
+            0 => 100,
+            den => u64::from(den),
+        };
+        Duration::from_nanos(u64::from(self.delay_num) * 1_000_000_000 / den)
+    }
+    /// The 26 bytes of fcTL chunk data
+    pub fn as_bytes(&self) -> [u8; FrameControl::LENGTH] {
+        let mut bytes = [0; FrameControl::LENGTH];
+        bytes[0..4].copy_from_slice(&self.sequence_number.to_be_bytes());
+        bytes[4..8].copy_from_slice(&self.width.to_be_bytes());
+        bytes[8..12].copy_from_slice(&self.height.to_be_bytes());
+        bytes[12..16].copy_from_slice(&self.x_offset.to_be_bytes());
+        bytes[16..20].copy_from_slice(&self.y_offset.to_be_bytes());
+        bytes[20..22].copy_from_slice(&self.delay_num.to_be_bytes());
+        bytes[22..24].copy_from_slice(&self.delay_den.to_be_bytes());
+        bytes[24] = self.dispose_op as u8;
+        bytes[25] = self.blend_op as u8;
+        bytes
+    }
+    /// Build the fcTL chunk holding this control
+    pub fn to_chunk(&self) -> Chunk {
+        Chunk::new(ChunkType::fcTL, self.as_bytes().to_vec())
+    }
+}
+
+impl TryFrom<&[u8]> for FrameControl {
+    type Error = PngError;
+
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        if value.len() != FrameControl::LENGTH {
+            return Err(invalid(format!(
+                "fcTL is {} bytes, expected {}",
+                value.len(),
+                FrameControl::LENGTH
+            )));
+        }
+        let dispose_op = match value[24] {
+            0 => DisposeOp::None,
+            1 => DisposeOp::Background,
+            2 => DisposeOp::Previous,
+            other => return Err(invalid(format!("unknown dispose op {other}"))),
+        };
+        let blend_op = match value[25] {
+            0 => BlendOp::Source,
+            1 => BlendOp::Over,
+            other => return Err(invalid(format!("unknown blend op {other}"))),
+        };
+        Ok(FrameControl {
+            sequence_number: word(value, 0),
+            width: word(value, 4),
+            height: word(value, 8),
+            x_offset: word(value, 12),
+            y_offset: word(value, 16),
+            delay_num: u16::from_be_bytes([value[20], value[21]]),
+            delay_den: u16::from_be_bytes([value[22], value[23]]),
+            dispose_op,
+            blend_op,
+        })
+    }
+}
+
+impl Display for FrameControl {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}x{} at ({}, {}), {}ms, dispose {}, blend {}",
+            self.width,
+            self.height,
+            self.x_offset,
+            self.y_offset,
+            self.delay().as_millis(),
+            self.dispose_op,
+            self.blend_op
+        )
+    }
+}
+
+/// One frame of an animation.
+pub struct Frame {
+    pub control: FrameControl,
+    /// Index of the frame's fcTL chunk
+    pub index: usize,
+    /// zlib compressed image data, joined from the IDAT or fdAT chunks
+    data: Vec<u8>,
+}
+
+impl Frame {
+    /// zlib compressed image data of the frame's region
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+}
+
+/// The animation stored in a png.
+pub struct Animation {
+    pub control: AnimationControl,
+    pub frames: Vec<Frame>,
+    /// Whether the IDAT image is the first frame. If it isn't, it is only
+    /// shown by decoders that don't support APNG.
+    pub default_image_is_frame: bool,
+}
+
+impl Animation {
+    /// Read the animation chunks of a png, checking that sequence numbers
+    /// run from 0 without gaps, that every frame fits on the canvas and has
+    /// image data, and that acTL agrees with the number of frames
+    pub fn parse(png: &Png) -> Result<Animation, PngError> {
+        let header = png.header()?;
+        let mut control: Option<(usize, AnimationControl)> = None;
+        let mut frames: Vec<Frame> = Vec::new();
+        let mut default_image_is_frame = false;
+        let mut seen_image_data = false;
+        let mut sequence = 0;
+        for (index, chunk) in png.chunks().iter().enumerate() {
+            let locate = |err: PngError| err.at(png.chunk_offset(index), index);
+            let fail = |reason: String| locate(invalid(reason));
+            let mut next_sequence = |found: u32| {
+                let expected = sequence;
+                sequence += 1;
+                match found == expected {
+                    true => Ok(()),
+                    false => Err(fail(format!(
+                        "sequence number is {found}, expected {expected}"
+                    ))),
+                }
+            };
+            match *chunk.chunk_type() {
+                ChunkType::acTL => {
+                    if control.is_some() {
+                        return Err(fail("there is more than one acTL chunk".to_string()));
+                    }
+                    if seen_image_data {
+                        return Err(fail("acTL comes after the image data".to_string()));
+                    }
+                    let parsed = AnimationControl::try_from(chunk.data()).map_err(locate)?;
+                    control = Some((index, parsed));
+                }
+                ChunkType::fcTL => {
+                    let frame = FrameControl::try_from(chunk.data()).map_err(locate)?;
+                    next_sequence(frame.sequence_number)?;
+                    if frame.width == 0 || frame.height == 0 {
+                        return Err(fail(format!("frame is {}x{}", frame.width, frame.height)));
+                    }
+                    let right = u64::from(frame.x_offset) + u64::from(frame.width);
+                    let bottom = u64::from(frame.y_offset) + u64::from(frame.height);
+                    if right > u64::from(header.width) || bottom > u64::from(header.height) {
+                        return Err(fail(format!(
+                            "frame {frame} doesn't fit on the {}x{} canvas",
+                            header.width, header.height
+                        )));
+                    }
+                    if !seen_image_data {
+                        if !frames.is_empty() {
+                            return Err(fail("two frames start before the image data".to_string()));
+                        }
+                        if (frame.width, frame.height, frame.x_offset, frame.y_offset)
+                            != (header.width, header.height, 0, 0)
+                        {
+                            return Err(fail(format!(
+                                "the first frame is the image data, so it must cover the \
+                                 whole canvas, but it is {frame}"
+                            )));
+                        }
+                        default_image_is_frame = true;
+                    }
+                    frames.push(Frame {
+                        control: frame,
+                        index,
+                        data: Vec::new(),
+                    });
+                }
+                ChunkType::IDAT => {
+                    seen_image_data = true;
+                    if default_image_is_frame && frames.len() == 1 {
+                        frames[0].data.extend_from_slice(chunk.data());
+                    }
+                }
+                ChunkType::fdAT => {
+                    if chunk.data().len() < 4 {
+                        return Err(fail("fdAT is too short for a sequence number".to_string()));
+                    }
+                    next_sequence(word(chunk.data(), 0))?;
+                    if default_image_is_frame && frames.len() == 1 {
+                        return Err(fail(
+                            "fdAT belongs to the first frame, which is the image data".to_string(),
+                        ));
+                    }
+                    match frames.last_mut() {
+                        Some(frame) => frame.data.extend_from_slice(&chunk.data()[4..]),
+                        None => return Err(fail("fdAT comes before any fcTL".to_string())),
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let (index, control) = control.ok_or_else(|| PngError::ChunkNotFound {
+            chunk_type: "acTL".to_string(),
+            offset: png.byte_len(),
+            index: png.chunks().len(),
+        })?;
+        if control.num_frames as usize != frames.len() || frames.is_empty() {
+            return Err(invalid(format!(
+                "acTL says there are {} frames but there are {}",
+                control.num_frames,
+                frames.len()
+            ))
+            .at(png.chunk_offset(index), index));
+        }
+        if let Some(frame) = frames.iter().find(|frame| frame.data.is_empty()) {
+            return Err(invalid("frame has no image data".to_string())
+                .at(png.chunk_offset(frame.index), frame.index));
+        }
+        Ok(Animation {
+            control,
+            frames,
+            default_image_is_frame,
+        })
+    }
+    /// Decode every frame and draw it on the canvas, applying the blend and
+    /// dispose operations. Returns the canvas as it looks while each frame
+    /// is shown.
+    pub fn render(&self, png: &Png) -> Result<Vec<Image>, PngError> {
+        let header = png.header()?;
+        let width = header.width as usize;
+        let mut canvas = vec![0; width * header.height as usize * Image::BYTES_PER_PIXEL];
+        let mut rendered = Vec::with_capacity(self.frames.len());
+        for (number, frame) in self.frames.iter().enumerate() {
+            let control = &frame.control;
+            let locate = |err: PngError| match err.offset() {
+                0 => err.at(png.chunk_offset(frame.index), frame.index),
+                _ => err,
+            };
+            let frame_header = ImageHeader {
+                width: control.width,
+                height: control.height,
+                ..header
+            };
+            let raw = image::decode_scanlines(&frame.data, &frame_header).map_err(locate)?;
+            let image = Image::from_scanlines(png, &frame_header, &raw).map_err(locate)?;
+
+            // a first frame can't go back to a canvas from before it existed
+            let dispose = match (number, control.dispose_op) {
+                (0, DisposeOp::Previous) => DisposeOp::Background,
+                (_, dispose) => dispose,
+            };
+            let saved = (dispose == DisposeOp::Previous).then(|| canvas.clone());
+            let region = |x: u32, y: u32| {
+                let x = (control.x_offset + x) as usize;
+                let y = (control.y_offset + y) as usize;
+                (y * width + x) * Image::BYTES_PER_PIXEL
+            };
+            for y in 0..control.height {
+                for x in 0..control.width {
+                    let source = image.pixel(x, y).expect("pixel is inside the frame");
+                    let at = region(x, y);
+                    let target = &mut canvas[at..at + Image::BYTES_PER_PIXEL];
+                    match control.blend_op {
+                        BlendOp::Source => target.copy_from_slice(&source),
+                        BlendOp::Over => over(source, target),
+                    }
+                }
+            }
+            rendered.push(Image::from_rgba(
+                header.width,
+                header.height,
+                canvas.clone(),
+            )?);
+
+            match (dispose, saved) {
+                (DisposeOp::Background, _) => {
+                    for y in 0..control.height {
+                        let start = region(0, y);
+                        let end = start + control.width as usize * Image::BYTES_PER_PIXEL;
+                        canvas[start..end].fill(0);
+                    }
+                }
+                (DisposeOp::Previous, Some(saved)) => canvas = saved,
+                _ => {}
+            }
+        }
+        Ok(rendered)
+    }
+}
+
+/// Alpha composite an RGBA pixel over another, as the APNG spec describes
+fn over(source: [u8; 4], target: &mut [u8]) {
+    match source[3] {
+        255 => target.copy_from_slice(&source),
+        0 => {}
+        alpha => {
+            let u = u32::from(alpha) * 255;
+            let v = u32::from(255 - alpha) * u32::from(target[3]);
+            let total = u + v;
+            for channel in 0..3 {
+                let mixed = u32::from(source[channel]) * u + u32::from(target[channel]) * v;
+                target[channel] = (mixed / total) as u8;
+            }
+            target[3] = (total / 255) as u8;
+        }
+    }
+}
+
+fn word(bytes: &[u8], at: usize) -> u32 {
+    u32::from_be_bytes([bytes[at], bytes[at + 1], bytes[at + 2], bytes[at + 3]])
+}
+
+fn invalid(reason: String) -> PngError {
+    PngError::InvalidAnimation {
+        reason,
+        offset: 0,
+        index: 0,
+    }
+}
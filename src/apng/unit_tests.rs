@@ -0,0 +1,233 @@
+use super::*;
+use crate::{
+    image_header::ColorType,
+    test_utils::{chunk, header},
+    zlib,
+};
+
+const RED: [u8; 4] = [255, 0, 0, 255];
+const GREEN: [u8; 4] = [0, 255, 0, 255];
+const CLEAR: [u8; 4] = [0, 0, 0, 0];
+
+fn control(sequence_number: u32, size: u32, offset: u32) -> FrameControl {
+    FrameControl {
+        sequence_number,
+        width: size,
+        height: size,
+        x_offset: offset,
+        y_offset: offset,
+        delay_num: 1,
+        delay_den: 10,
+        dispose_op: DisposeOp::None,
+        blend_op: BlendOp::Source,
+    }
+}
+
+/// Compressed unfiltered scanlines of a square frame of one color
+fn pixels(size: usize, pixel: [u8; 4]) -> Vec<u8> {
+    let mut row = vec![0];
+    (0..size).for_each(|_| row.extend_from_slice(&pixel));
+    let raw = row.repeat(size);
+    zlib::deflate(&raw, 6)
+}
+
+fn fdat(sequence_number: u32, data: &[u8]) -> Chunk {
+    let mut bytes = sequence_number.to_be_bytes().to_vec();
+    bytes.extend_from_slice(data);
+    chunk("fdAT", &bytes)
+}
+
+/// A red 2x2 first frame, then a half transparent blue pixel drawn over
+/// the bottom right and cleared, then a green pixel in the top left that
+/// is put back
+fn animated_png() -> Png {
+    let second = FrameControl {
+        blend_op: BlendOp::Over,
+        dispose_op: DisposeOp::Background,
+        ..control(1, 1, 1)
+    };
+    let third = FrameControl {
+        dispose_op: DisposeOp::Previous,
+        ..control(3, 1, 0)
+    };
+    Png::from_chunks(vec![
+        header(2, 2, 8, ColorType::Rgba).to_chunk(),
+        AnimationControl {
+            num_frames: 3,
+            num_plays: 0,
+        }
+        .to_chunk(),
+        control(0, 2, 0).to_chunk(),
+        chunk("IDAT", &pixels(2, RED)),
+        second.to_chunk(),
+        fdat(2, &pixels(1, [0, 0, 255, 128])),
+        third.to_chunk(),
+        fdat(4, &pixels(1, GREEN)),
+        chunk("IEND", &[]),
+    ])
+}
+
+#[test]
+fn test_frame_control_round_trip() {
+    let frame = FrameControl {
+        dispose_op: DisposeOp::Previous,
+        blend_op: BlendOp::Over,
+        ..control(7, 3, 1)
+    };
+    assert_eq!(
+        FrameControl::try_from(&frame.as_bytes()[..]).unwrap(),
+        frame
+    );
+    assert_eq!(
+        frame.to_string(),
+        "3x3 at (1, 1), 100ms, dispose previous, blend over"
+    );
+}
+
+#[test]
+fn test_zero_delay_denominator_means_hundredths() {
+    let frame = FrameControl {
+        delay_num: 5,
+        delay_den: 0,
+        ..control(0, 1, 0)
+    };
+    assert_eq!(frame.delay(), Duration::from_millis(50));
+}
+
+#[test]
+fn test_unknown_dispose_op() {
+    let mut bytes = control(0, 1, 0).as_bytes();
+    bytes[24] = 3;
+    let err = FrameControl::try_from(&bytes[..]).unwrap_err();
+    assert!(err.to_string().contains("unknown dispose op 3"));
+}
+
+#[test]
+fn test_parse() {
+    let animation = animated_png().animation().unwrap();
+    assert_eq!(animation.control.to_string(), "3 frames, plays forever");
+    assert!(animation.default_image_is_frame);
+    let indexes: Vec<usize> = animation.frames.iter().map(|frame| frame.index).collect();
+    assert_eq!(indexes, [2, 4, 6]);
+    assert_eq!(animation.frames[0].data(), pixels(2, RED));
+    assert_eq!(animation.frames[2].data(), pixels(1, GREEN));
+}
+
+#[test]
+fn test_default_image_outside_animation() {
+    let png = Png::from_chunks(vec![
+        header(2, 2, 8, ColorType::Rgba).to_chunk(),
+        AnimationControl {
+            num_frames: 1,
+            num_plays: 2,
+        }
+        .to_chunk(),
+        chunk("IDAT", &pixels(2, RED)),
+        control(0, 1, 1).to_chunk(),
+        fdat(1, &pixels(1, GREEN)),
+        chunk("IEND", &[]),
+    ]);
+    let animation = png.animation().unwrap();
+    assert!(!animation.default_image_is_frame);
+    assert_eq!(animation.frames.len(), 1);
+
+    let frames = animation.render(&png).unwrap();
+    assert_eq!(frames[0].pixels(), [CLEAR, CLEAR, CLEAR, GREEN].concat());
+}
+
+#[test]
+fn test_render() {
+    let png = animated_png();
+    let frames = png.animation().unwrap().render(&png).unwrap();
+    assert_eq!(frames.len(), 3);
+    assert_eq!(frames[0].pixels(), [RED, RED, RED, RED].concat());
+    assert_eq!(
+        frames[1].pixels(),
+        [RED, RED, RED, [127, 0, 128, 255]].concat()
+    );
+    assert_eq!(frames[2].pixels(), [GREEN, RED, RED, CLEAR].concat());
+}
+
+#[test]
+fn test_missing_animation_control() {
+    let png = Png::from_chunks(vec![
+        header(2, 2, 8, ColorType::Rgba).to_chunk(),
+        chunk("IDAT", &pixels(2, RED)),
+        chunk("IEND", &[]),
+    ]);
+    assert!(matches!(
+        png.animation().err().unwrap(),
+        PngError::ChunkNotFound { index: 3, .. }
+    ));
+}
+
+#[test]
+fn test_out_of_order_sequence_number() {
+    let mut chunks: Vec<Chunk> = animated_png()
+        .chunks()
+        .iter()
+        .map(|chunk| Chunk::new(*chunk.chunk_type(), chunk.data().to_vec()))
+        .collect();
+    chunks[7] = fdat(5, &pixels(1, GREEN));
+    let png = Png::from_chunks(chunks);
+    let err = png.animation().err().unwrap();
+    assert_eq!(err.index(), 7);
+    assert_eq!(err.offset(), png.chunk_offset(7));
+    assert!(err.to_string().contains("sequence number is 5, expected 4"));
+}
+
+#[test]
+fn test_frame_count_mismatch() {
+    let mut chunks: Vec<Chunk> = animated_png()
+        .chunks()
+        .iter()
+        .map(|chunk| Chunk::new(*chunk.chunk_type(), chunk.data().to_vec()))
+        .collect();
+    chunks[1] = AnimationControl {
+        num_frames: 2,
+        num_plays: 0,
+    }
+    .to_chunk();
+    let err = Png::from_chunks(chunks).animation().err().unwrap();
+    assert_eq!(err.index(), 1);
+    assert!(err
+        .to_string()
+        .contains("acTL says there are 2 frames but there are 3"));
+}
+
+#[test]
+fn test_frame_outside_canvas() {
+    let png = Png::from_chunks(vec![
+        header(2, 2, 8, ColorType::Rgba).to_chunk(),
+        AnimationControl {
+            num_frames: 1,
+            num_plays: 0,
+        }
+        .to_chunk(),
+        chunk("IDAT", &pixels(2, RED)),
+        control(0, 2, 1).to_chunk(),
+        fdat(1, &pixels(2, GREEN)),
+        chunk("IEND", &[]),
+    ]);
+    let err = png.animation().err().unwrap();
+    assert_eq!(err.index(), 3);
+    assert!(err.to_string().contains("doesn't fit on the 2x2 canvas"));
+}
+
+#[test]
+fn test_frame_without_data() {
+    let png = Png::from_chunks(vec![
+        header(2, 2, 8, ColorType::Rgba).to_chunk(),
+        AnimationControl {
+            num_frames: 1,
+            num_plays: 0,
+        }
+        .to_chunk(),
+        chunk("IDAT", &pixels(2, RED)),
+        control(0, 1, 0).to_chunk(),
+        chunk("IEND", &[]),
+    ]);
+    let err = png.animation().err().unwrap();
+    assert_eq!(err.index(), 3);
+    assert!(err.to_string().contains("frame has no image data"));
+}
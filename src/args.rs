@@ -0,0 +1,339 @@
+//! Command line arguments for the `pngame` binary.
+
+use std::path::PathBuf;
+
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use pngame::payload;
+
+/// Hide and retrieve secret messages inside PNG files.
+#[derive(Parser, Debug)]
+#[command(author, version, about)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Hide a message in a new chunk of the given type
+    Encode(EncodeArgs),
+    /// Print the message stored in chunks of the given type
+    Decode(DecodeArgs),
+    /// Remove the first chunk of the given type, or all of them
+    Remove(RemoveArgs),
+    /// Print the chunk types contained in a PNG file
+    Print(PrintArgs),
+    /// Check a PNG file against the chunk ordering rules of the spec
+    Check(CheckArgs),
+    /// Salvage the readable chunks of a damaged PNG file
+    Recover(RecoverArgs),
+    /// Read and edit tEXt, zTXt and iTXt metadata
+    Text(TextArgs),
+    /// Rewrite the image data with Adam7 interlacing, or without it
+    Interlace(InterlaceArgs),
+    /// Print the size of each Adam7 pass of an interlaced PNG file
+    Passes(PassesArgs),
+    /// Print how long a message can be hidden in the pixels of a PNG file
+    Capacity(CapacityArgs),
+    /// Make a PNG file smaller without changing its pixels
+    Optimize(OptimizeArgs),
+    /// Remove metadata, keeping only what a profile allows
+    Strip(StripArgs),
+    /// List the frames of an animated PNG file, or save each as a PNG file
+    Frames(FramesArgs),
+    /// List every chunk of a PNG file, optionally as JSON
+    Dump(DumpArgs),
+    /// Write a PNG file from the JSON printed by `dump --json`
+    Build(BuildArgs),
+    /// Print a table of every chunk with its CRC status and decoded contents
+    Inspect(InspectArgs),
+    /// Show which chunks were added, removed, moved or changed between two
+    /// PNG files, failing if there are any
+    Diff(DiffArgs),
+    /// Describe a chunk type: what the spec says about it and what its
+    /// property bits mean
+    Explain(ExplainArgs),
+}
+
+/// Where a message is hidden
+#[derive(ValueEnum, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Method {
+    /// In chunks of the given type
+    Chunk,
+    /// In the least significant bits of the pixels, tagged with the given
+    /// chunk type
+    Lsb,
+}
+
+#[derive(Args, Debug)]
+pub struct EncodeArgs {
+    /// PNG file to read
+    pub file: PathBuf,
+    /// 4 letter chunk type to store the message in, e.g. `ruSt`
+    pub chunk_type: String,
+    /// Message to hide, or `-` to read it from stdin
+    pub message: String,
+    /// Where to write the result, defaults to overwriting `file`
+    pub output: Option<PathBuf>,
+    /// Encrypt the message with this passphrase
+    #[arg(long)]
+    pub passphrase: Option<String>,
+    /// Split the message across several chunks of at most this many bytes
+    #[arg(long, value_name = "BYTES")]
+    pub split: Option<usize>,
+    /// zlib compress the message before storing it
+    #[arg(long)]
+    pub compress: bool,
+    /// Where to hide the message
+    #[arg(long, value_enum, default_value_t = Method::Chunk)]
+    pub method: Method,
+    /// Scatter the message over pixels chosen with this key (lsb only)
+    #[arg(long)]
+    pub key: Option<String>,
+}
+
+#[derive(Args, Debug)]
+pub struct DecodeArgs {
+    /// PNG file to read
+    pub file: PathBuf,
+    /// 4 letter chunk type the message is stored in
+    pub chunk_type: String,
+    /// Decrypt the message with this passphrase
+    #[arg(long)]
+    pub passphrase: Option<String>,
+    /// Write the message to this file instead of printing it
+    #[arg(long)]
+    pub output: Option<PathBuf>,
+    /// Refuse to decompress messages larger than this many bytes
+    #[arg(long, value_name = "BYTES", default_value_t = payload::DEFAULT_MAX_INFLATED)]
+    pub max_size: u64,
+    /// Where the message is hidden
+    #[arg(long, value_enum, default_value_t = Method::Chunk)]
+    pub method: Method,
+    /// Key the message was scattered over the pixels with (lsb only)
+    #[arg(long)]
+    pub key: Option<String>,
+}
+
+#[derive(Args, Debug)]
+pub struct RemoveArgs {
+    /// PNG file to modify in place
+    pub file: PathBuf,
+    /// 4 letter chunk type to remove
+    pub chunk_type: String,
+    /// Remove every chunk of this type, not just the first
+    #[arg(long)]
+    pub all: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct PrintArgs {
+    /// PNG file to read
+    pub file: PathBuf,
+}
+
+#[derive(Args, Debug)]
+pub struct CheckArgs {
+    /// PNG file to check
+    pub file: PathBuf,
+}
+
+#[derive(Args, Debug)]
+pub struct RecoverArgs {
+    /// Damaged PNG file to read
+    pub file: PathBuf,
+    /// Where to write the salvaged chunks, if anywhere
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+pub struct TextArgs {
+    #[command(subcommand)]
+    pub command: TextCommand,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum TextCommand {
+    /// Print every text entry
+    List(TextListArgs),
+    /// Print the text stored under a keyword
+    Get(TextGetArgs),
+    /// Store text under a keyword, replacing any existing entry
+    Set(TextSetArgs),
+    /// Remove every entry with a keyword
+    Remove(TextRemoveArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct TextListArgs {
+    /// PNG file to read
+    pub file: PathBuf,
+}
+
+#[derive(Args, Debug)]
+pub struct TextGetArgs {
+    /// PNG file to read
+    pub file: PathBuf,
+    /// Keyword such as `Title`, `Author` or `Comment`
+    pub keyword: String,
+}
+
+#[derive(Args, Debug)]
+pub struct TextSetArgs {
+    /// PNG file to read
+    pub file: PathBuf,
+    /// Keyword such as `Title`, `Author` or `Comment`
+    pub keyword: String,
+    /// Text to store
+    pub text: String,
+    /// Where to write the result, defaults to overwriting `file`
+    pub output: Option<PathBuf>,
+    /// Compress the text (zTXt, or compressed iTXt)
+    #[arg(long)]
+    pub compress: bool,
+    /// Store as UTF-8 iTXt. Implied by the options below, or by text that
+    /// can't be written as Latin-1.
+    #[arg(long)]
+    pub international: bool,
+    /// Language of the text, e.g. `en-GB`
+    #[arg(long)]
+    pub language: Option<String>,
+    /// The keyword translated into the language of the text
+    #[arg(long)]
+    pub translated_keyword: Option<String>,
+}
+
+#[derive(Args, Debug)]
+pub struct TextRemoveArgs {
+    /// PNG file to modify in place
+    pub file: PathBuf,
+    /// Keyword to remove
+    pub keyword: String,
+}
+
+#[derive(Args, Debug)]
+pub struct InterlaceArgs {
+    /// PNG file to read
+    pub file: PathBuf,
+    /// Where to write the result, defaults to overwriting `file`
+    pub output: Option<PathBuf>,
+    /// Remove interlacing instead of adding it
+    #[arg(long)]
+    pub remove: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct PassesArgs {
+    /// PNG file to read
+    pub file: PathBuf,
+}
+
+#[derive(Args, Debug)]
+pub struct CapacityArgs {
+    /// PNG file to read
+    pub file: PathBuf,
+}
+
+#[derive(Args, Debug)]
+pub struct OptimizeArgs {
+    /// PNG file to read
+    pub file: PathBuf,
+    /// Where to write the result, defaults to overwriting `file`
+    pub output: Option<PathBuf>,
+    /// Drop every ancillary chunk that isn't listed with --keep
+    #[arg(long)]
+    pub strip: bool,
+    /// Ancillary chunk types to keep, e.g. `tEXt,pHYs`. Implies --strip.
+    #[arg(long, value_delimiter = ',', value_name = "TYPES")]
+    pub keep: Vec<String>,
+    /// Only try these zlib levels, from 0 to 9
+    #[arg(long = "level", value_name = "LEVEL", value_parser = clap::value_parser!(u32).range(0..=9))]
+    pub levels: Vec<u32>,
+}
+
+/// Which ancillary chunks `strip` keeps before any --keep options
+#[derive(ValueEnum, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum StripProfile {
+    /// Remove every ancillary chunk that isn't part of the image
+    All,
+    /// Keep cHRM, gAMA, iCCP, sBIT, sRGB and other color management chunks
+    Color,
+}
+
+#[derive(Args, Debug)]
+pub struct StripArgs {
+    /// PNG file to read
+    pub file: PathBuf,
+    /// Where to write the result, defaults to overwriting `file`
+    pub output: Option<PathBuf>,
+    /// What to keep
+    #[arg(long, value_enum, default_value_t = StripProfile::All)]
+    pub profile: StripProfile,
+    /// Also keep text chunks with these keywords, e.g. `Title,Copyright`
+    #[arg(long, value_delimiter = ',', value_name = "KEYWORDS")]
+    pub keep_text: Vec<String>,
+    /// Also keep chunks of these types, e.g. `pHYs,tIME`
+    #[arg(long, value_delimiter = ',', value_name = "TYPES")]
+    pub keep: Vec<String>,
+    /// List what would be removed without changing anything
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct FramesArgs {
+    /// PNG file to read
+    pub file: PathBuf,
+    /// Directory to write each frame to, as it looks when it's shown
+    #[arg(long, value_name = "DIR")]
+    pub extract: Option<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+pub struct DumpArgs {
+    /// PNG file to read
+    pub file: PathBuf,
+    /// Print JSON that `build` can turn back into a PNG file
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct BuildArgs {
+    /// JSON file to read, or `-` to read it from stdin
+    pub json: PathBuf,
+    /// Where to write the PNG file
+    pub output: PathBuf,
+}
+
+#[derive(Args, Debug)]
+pub struct InspectArgs {
+    /// PNG file to read
+    pub file: PathBuf,
+    /// Also print a hex dump of each chunk's data
+    #[arg(long)]
+    pub hex: bool,
+    /// Show at most this many bytes of each chunk in the hex dump, 0 for all
+    #[arg(long, value_name = "BYTES", default_value_t = 256)]
+    pub hex_limit: usize,
+}
+
+#[derive(Args, Debug)]
+pub struct DiffArgs {
+    /// PNG file to compare from
+    pub old: PathBuf,
+    /// PNG file to compare to
+    pub new: PathBuf,
+    /// Chunk types to leave out of the comparison, e.g. `tIME,IDAT`
+    #[arg(long, value_delimiter = ',', value_name = "TYPES")]
+    pub ignore: Vec<String>,
+    /// Print the differences as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct ExplainArgs {
+    /// Chunk type to describe, e.g. `tEXt`
+    pub chunk_type: String,
+}
@@ -38,10 +38,9 @@
 #[cfg(test)]
 mod unit_tests;
 
-use std::{fmt::Display, str};
+use std::{fmt::Display, io::Write, str};
 
-use crate::chunk_type::ChunkType;
-use anyhow::{anyhow, Error, Result};
+use crate::{chunk_type::ChunkType, error::PngError};
 use crc::{Crc, CRC_32_ISO_HDLC};
 
 ///  PNG files are essentially just a list of "chunks", each containing their
@@ -53,9 +52,14 @@ pub struct Chunk {
     chunk_type: ChunkType,
     data: Vec<u8>,
     crc: Crc<u32>,
+    /// CRC read from a file, kept as is even if it is wrong
+    raw_crc: Option<u32>,
 }
 
 impl Chunk {
+    /// The largest length a chunk's data may have, 2^31 - 1 bytes
+    pub const MAX_LENGTH: u32 = (1 << 31) - 1;
+
     pub fn new(chunk_type: ChunkType, data: Vec<u8>) -> Self {
         // http://justsolve.archiveteam.org/wiki/CRC-32
         let crc = Crc::<u32>::new(&CRC_32_ISO_HDLC);
@@ -63,15 +67,27 @@ impl Chunk {
             chunk_type,
             data,
             crc,
+            raw_crc: None,
+        }
+    }
+    /// Create a chunk that keeps the CRC it was read with, even if that
+    /// doesn't match its contents, so that it is written back out unchanged
+    pub fn with_raw_crc(chunk_type: ChunkType, data: Vec<u8>, raw_crc: u32) -> Self {
+        Chunk {
+            raw_crc: Some(raw_crc),
+            ..Chunk::new(chunk_type, data)
         }
     }
     /// A 4-byte unsigned integer giving the number of bytes in the chunk's
     /// data field. The length counts only the data field, not itself, the
     /// chunk type code, or the CRC. Zero is a valid length. Although encoders
     /// and decoders should treat the length as unsigned, its value must not
-    /// exceed 231 bytes.
+    /// exceed 2^31 - 1 bytes.
+    ///
+    /// Data too long to fit in a `u32` reports `u32::MAX`, which is past
+    /// [`Chunk::MAX_LENGTH`] and so is refused when written.
     pub fn length(&self) -> u32 {
-        self.data.len().try_into().unwrap()
+        self.data.len().try_into().unwrap_or(u32::MAX)
     }
     pub fn chunk_type(&self) -> &ChunkType {
         &self.chunk_type
@@ -83,15 +99,34 @@ impl Chunk {
     /// bytes in the chunk, including the chunk type code and chunk data
     /// fields, but not including the length field. The CRC is always present,
     /// even for chunks containing no data.
-    fn crc(&self) -> u32 {
-        let evaluation_bytes: Vec<u8> =
-            [self.chunk_type.bytes().to_vec(), self.data.clone()].concat();
-        self.crc.checksum(&evaluation_bytes)
+    pub fn crc(&self) -> u32 {
+        let mut digest = self.crc.digest();
+        digest.update(&self.chunk_type.bytes());
+        digest.update(&self.data);
+        digest.finalize()
+    }
+    /// The CRC this chunk was read with, if it was created with
+    /// [`Chunk::with_raw_crc`]
+    pub fn raw_crc(&self) -> Option<u32> {
+        self.raw_crc
     }
-    pub fn data_as_string(&self) -> Result<String> {
+    /// Forget the raw CRC, so that the calculated one is written out
+    pub fn reset_crc(&mut self) {
+        self.raw_crc = None;
+    }
+    /// The CRC that is written out for this chunk: the raw CRC if there is
+    /// one, otherwise the calculated one
+    pub fn written_crc(&self) -> u32 {
+        self.raw_crc.unwrap_or_else(|| self.crc())
+    }
+    pub fn data_as_string(&self) -> Result<String, PngError> {
         match str::from_utf8(self.data()) {
             Ok(s) => Ok(s.to_string()),
-            Err(err) => Err(err.into()),
+            Err(source) => Err(PngError::InvalidUtf8 {
+                source,
+                offset: 0,
+                index: 0,
+            }),
         }
     }
     pub fn as_bytes(&self) -> Vec<u8> {
@@ -100,45 +135,73 @@ impl Chunk {
             .iter()
             .chain(self.chunk_type.bytes().iter())
             .chain(self.data.iter())
-            .chain(self.crc().to_be_bytes().iter())
+            .chain(self.written_crc().to_be_bytes().iter())
             .copied()
             .collect()
     }
+    /// Write the length, chunk type, data and CRC without building an
+    /// intermediate buffer
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        writer.write_all(&self.length().to_be_bytes())?;
+        writer.write_all(&self.chunk_type.bytes())?;
+        writer.write_all(&self.data)?;
+        writer.write_all(&self.written_crc().to_be_bytes())
+    }
+    /// Split the first chunk off the front of `bytes` without checking its
+    /// CRC. Returns the chunk, holding the CRC it was stored with, and the
+    /// number of bytes it took up.
+    pub fn parse_raw(bytes: &[u8]) -> Result<(Chunk, usize), PngError> {
+        let truncated = |needed: usize| PngError::TruncatedChunk {
+            needed: needed as u64,
+            available: bytes.len() as u64,
+            offset: 0,
+            index: 0,
+        };
+        if bytes.len() < 8 {
+            return Err(truncated(12));
+        }
+        let (start, rest) = bytes.split_at(8);
+        let length = u32::from_be_bytes([start[0], start[1], start[2], start[3]]);
+        if length > Chunk::MAX_LENGTH {
+            return Err(PngError::LengthOverflow {
+                length: length.into(),
+                offset: 0,
+                index: 0,
+            });
+        }
+        let length = length as usize;
+        let chunk_type = ChunkType::try_from([start[4], start[5], start[6], start[7]])?;
+        if length + 4 > rest.len() {
+            return Err(truncated(length + 12));
+        };
+        let (data, rest) = rest.split_at(length);
+        let crc = u32::from_be_bytes([rest[0], rest[1], rest[2], rest[3]]);
+        Ok((
+            Chunk::with_raw_crc(chunk_type, data.to_vec(), crc),
+            length + 12,
+        ))
+    }
+    /// Check that the raw CRC, if there is one, matches the contents
+    pub fn verify_crc(&self) -> Result<(), PngError> {
+        match self.raw_crc {
+            Some(raw_crc) if raw_crc != self.crc() => Err(PngError::CrcMismatch {
+                chunk_type: self.chunk_type.to_string(),
+                expected: self.crc(),
+                actual: raw_crc,
+                offset: 0,
+                index: 0,
+            }),
+            _ => Ok(()),
+        }
+    }
 }
 
 impl TryFrom<&Vec<u8>> for Chunk {
-    type Error = Error;
+    type Error = PngError;
     /// Take a byte vec and split out the chunk elements
-    fn try_from(value: &Vec<u8>) -> std::result::Result<Self, Self::Error> {
-        if value.len() < 8 {
-            return Err(anyhow!(
-                "not at least 8 bytes left to parse. Array len: {}",
-                value.len()
-            ));
-        }
-        let (start, rest) = value.split_at(8);
-        let length_bytes: [u8; 4] = start[0..4].try_into()?;
-        let length: usize = u32::from_be_bytes(length_bytes).try_into()?;
-        let chunk_type_bytes: [u8; 4] = start[4..8].try_into()?;
-        let chunk_type = ChunkType::try_from(chunk_type_bytes)?;
-        if length >= rest.len() {
-            return Err(anyhow!(
-                "not enough bytes left to parse. Length: {length}, rest: {}, array len: {}",
-                rest.len(),
-                value.len()
-            ));
-        };
-        let (data, rest) = rest.split_at(length);
-        let chunk = Chunk::new(chunk_type, data.try_into()?);
-        let crc_bytes: [u8; 4] = rest[0..4].try_into()?;
-        let crc = u32::from_be_bytes(crc_bytes);
-        if crc != chunk.crc() {
-            return Err(anyhow!(
-                "crc input {} didn't match calculated {}",
-                crc,
-                chunk.crc()
-            ));
-        };
+    fn try_from(value: &Vec<u8>) -> Result<Self, Self::Error> {
+        let (chunk, _) = Chunk::parse_raw(value)?;
+        chunk.verify_crc()?;
         Ok(chunk)
     }
 }
@@ -125,3 +125,64 @@ pub fn test_chunk_trait_impls() {
 
     let _chunk_string = format!("{}", chunk);
 }
+
+#[test]
+fn test_crc_mismatch_error() {
+    let mut chunk_data = testing_chunk().as_bytes();
+    let last = chunk_data.len() - 1;
+    chunk_data[last] ^= 1;
+
+    match Chunk::try_from(&chunk_data) {
+        Err(PngError::CrcMismatch {
+            expected, actual, ..
+        }) => {
+            assert_eq!(expected, 2882656334);
+            assert_eq!(actual, 2882656334 ^ 1);
+        }
+        other => panic!("expected crc mismatch, got {:?}", other.err()),
+    }
+}
+
+#[test]
+fn test_truncated_chunk_error() {
+    let chunk_data = testing_chunk().as_bytes();
+    let short = chunk_data[..4].to_vec();
+    assert!(matches!(
+        Chunk::try_from(&short),
+        Err(PngError::TruncatedChunk { needed: 12, .. })
+    ));
+    for len in [20, chunk_data.len() - 1] {
+        let truncated = chunk_data[..len].to_vec();
+        assert!(
+            matches!(
+                Chunk::try_from(&truncated),
+                Err(PngError::TruncatedChunk { needed: 54, .. })
+            ),
+            "truncated to {len}"
+        );
+    }
+}
+
+#[test]
+fn test_length_overflow_error() {
+    let mut chunk_data = testing_chunk().as_bytes();
+    chunk_data[0] = 0x80;
+    assert!(matches!(
+        Chunk::try_from(&chunk_data),
+        Err(PngError::LengthOverflow { .. })
+    ));
+}
+
+#[test]
+fn test_raw_crc_kept() {
+    let mut chunk_data = testing_chunk().as_bytes();
+    let last = chunk_data.len() - 1;
+    chunk_data[last] ^= 1;
+
+    let (chunk, length) = Chunk::parse_raw(&chunk_data).unwrap();
+    assert_eq!(length, chunk_data.len());
+    assert_eq!(chunk.raw_crc(), Some(2882656334 ^ 1));
+    assert_eq!(chunk.crc(), 2882656334);
+    assert!(chunk.verify_crc().is_err());
+    assert_eq!(chunk.as_bytes(), chunk_data);
+}
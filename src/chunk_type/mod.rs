@@ -8,7 +8,7 @@
 #[cfg(test)]
 mod unit_tests;
 
-use anyhow::{anyhow, Error};
+use crate::error::PngError;
 use std::str;
 
 /// Chunk type codes are assigned so that a decoder can determine some
@@ -20,35 +20,88 @@ use std::str;
 ///
 /// The naming rules are not normally of interest when the decoder does
 /// recognize the chunk's type.
-#[derive(PartialEq, Eq, Debug)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub struct ChunkType {
     raw: [u8; 4],
 }
 
+/// Every chunk type registered in PNG 1.2, the third edition and the
+/// extensions, spelled as in the spec so that the case of each letter still
+/// shows its property bit. See [`crate::registry`] for what they mean.
+#[allow(non_upper_case_globals)]
+impl ChunkType {
+    // Critical chunks of PNG 1.2
+    pub const IHDR: ChunkType = ChunkType { raw: *b"IHDR" };
+    pub const PLTE: ChunkType = ChunkType { raw: *b"PLTE" };
+    pub const IDAT: ChunkType = ChunkType { raw: *b"IDAT" };
+    pub const IEND: ChunkType = ChunkType { raw: *b"IEND" };
+
+    // Ancillary chunks of PNG 1.2
+    pub const tRNS: ChunkType = ChunkType { raw: *b"tRNS" };
+    pub const gAMA: ChunkType = ChunkType { raw: *b"gAMA" };
+    pub const cHRM: ChunkType = ChunkType { raw: *b"cHRM" };
+    pub const sRGB: ChunkType = ChunkType { raw: *b"sRGB" };
+    pub const iCCP: ChunkType = ChunkType { raw: *b"iCCP" };
+    pub const tEXt: ChunkType = ChunkType { raw: *b"tEXt" };
+    pub const zTXt: ChunkType = ChunkType { raw: *b"zTXt" };
+    pub const iTXt: ChunkType = ChunkType { raw: *b"iTXt" };
+    pub const bKGD: ChunkType = ChunkType { raw: *b"bKGD" };
+    pub const pHYs: ChunkType = ChunkType { raw: *b"pHYs" };
+    pub const sBIT: ChunkType = ChunkType { raw: *b"sBIT" };
+    pub const sPLT: ChunkType = ChunkType { raw: *b"sPLT" };
+    pub const hIST: ChunkType = ChunkType { raw: *b"hIST" };
+    pub const tIME: ChunkType = ChunkType { raw: *b"tIME" };
+
+    // Chunks added by the PNG third edition
+    pub const cICP: ChunkType = ChunkType { raw: *b"cICP" };
+    pub const mDCV: ChunkType = ChunkType { raw: *b"mDCV" };
+    pub const cLLI: ChunkType = ChunkType { raw: *b"cLLI" };
+    pub const eXIf: ChunkType = ChunkType { raw: *b"eXIf" };
+    pub const acTL: ChunkType = ChunkType { raw: *b"acTL" };
+    pub const fcTL: ChunkType = ChunkType { raw: *b"fcTL" };
+    pub const fdAT: ChunkType = ChunkType { raw: *b"fdAT" };
+
+    // Chunks registered in the PNG extensions
+    pub const oFFs: ChunkType = ChunkType { raw: *b"oFFs" };
+    pub const pCAL: ChunkType = ChunkType { raw: *b"pCAL" };
+    pub const sCAL: ChunkType = ChunkType { raw: *b"sCAL" };
+    pub const gIFg: ChunkType = ChunkType { raw: *b"gIFg" };
+    pub const gIFx: ChunkType = ChunkType { raw: *b"gIFx" };
+    pub const gIFt: ChunkType = ChunkType { raw: *b"gIFt" };
+    pub const sTER: ChunkType = ChunkType { raw: *b"sTER" };
+    pub const dSIG: ChunkType = ChunkType { raw: *b"dSIG" };
+    pub const fRAc: ChunkType = ChunkType { raw: *b"fRAc" };
+}
+
 impl TryFrom<[u8; 4]> for ChunkType {
-    type Error = Error;
+    type Error = PngError;
 
-    /// Check that all bytes are valid utf-8 and ASCII
+    /// Check that all bytes are ASCII letters
     fn try_from(value: [u8; 4]) -> std::result::Result<Self, Self::Error> {
-        match str::from_utf8(&value) {
-            Ok(_) => Ok(ChunkType { raw: value }),
-            Err(err) => Err(err.into()),
+        if value.iter().all(|byte| byte.is_ascii_alphabetic()) {
+            return Ok(ChunkType { raw: value });
         }
+        Err(PngError::InvalidChunkType {
+            bytes: value.to_vec(),
+            offset: 0,
+            index: 0,
+        })
     }
 }
 impl str::FromStr for ChunkType {
-    type Err = Error;
+    type Err = PngError;
 
-    /// Check that string is valid ASCII and that there are at least 4 bytes
+    /// Check that string is valid ASCII and that there are exactly 4 bytes
     /// to make a valid chunk type
     fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
-        let bytes: [u8; 4] = s.as_bytes().try_into()?;
-        if bytes.iter().fold(true, |valid, byte| {
-            (byte.is_ascii_lowercase() || byte.is_ascii_uppercase()) && valid
-        }) {
-            return Ok(ChunkType { raw: bytes });
+        match <[u8; 4]>::try_from(s.as_bytes()) {
+            Ok(bytes) => ChunkType::try_from(bytes),
+            Err(_) => Err(PngError::InvalidChunkType {
+                bytes: s.as_bytes().to_vec(),
+                offset: 0,
+                index: 0,
+            }),
         }
-        Err(anyhow!("chunk type: {:?} is invalid", bytes))
     }
 }
 impl std::fmt::Display for ChunkType {
@@ -66,9 +119,7 @@ impl ChunkType {
     }
     /// Check if chunk is valid ASCII and valid PNG
     pub fn is_valid(&self) -> bool {
-        self.bytes().iter().fold(true, |valid, byte| {
-            (byte.is_ascii_lowercase() || byte.is_ascii_uppercase()) && valid
-        }) && self.is_reserved_bit_valid()
+        self.bytes().iter().all(|byte| byte.is_ascii_alphabetic()) && self.is_reserved_bit_valid()
     }
     /// 0 (uppercase) = critical, 1 (lowercase) = ancillary.
     ///
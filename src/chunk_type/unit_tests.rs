@@ -93,3 +93,12 @@ pub fn test_chunk_type_trait_impls() {
     let _chunk_string = format!("{}", chunk_type_1);
     let _are_chunks_equal = chunk_type_1 == chunk_type_2;
 }
+
+#[test]
+pub fn test_chunk_type_invalid_bytes_error() {
+    let err = ChunkType::try_from([82, 117, 0, 116]).unwrap_err();
+    assert!(matches!(err, PngError::InvalidChunkType { bytes, .. } if bytes == [82, 117, 0, 116]));
+
+    let err = ChunkType::from_str("RuStY").unwrap_err();
+    assert!(matches!(err, PngError::InvalidChunkType { .. }));
+}
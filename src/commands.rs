@@ -0,0 +1,608 @@
+//! Implementations of the `pngame` subcommands.
+
+use std::{
+    fs::{self, File},
+    io::{self, BufWriter, Read},
+    path::Path,
+    str::FromStr,
+};
+
+use anyhow::{bail, Context, Result};
+use pngame::{
+    adam7,
+    chunk::Chunk,
+    chunk_type::ChunkType,
+    crypto, diff,
+    dump::{self, Dump},
+    error::PngError,
+    image_header::InterlaceMethod,
+    inspect::{self, CrcStatus},
+    lsb::{self, BitOrder},
+    optimize::{self, OptimizeOptions},
+    payload,
+    png::Png,
+    registry,
+    strip::{self, Policy},
+    text::{TextChunk, TextKind},
+    writer::PngWriter,
+};
+
+use crate::args::{
+    BuildArgs, CapacityArgs, CheckArgs, DecodeArgs, DiffArgs, DumpArgs, EncodeArgs, ExplainArgs,
+    FramesArgs, InspectArgs, InterlaceArgs, Method, OptimizeArgs, PassesArgs, PrintArgs,
+    RecoverArgs, RemoveArgs, StripArgs, StripProfile, TextArgs, TextCommand,
+};
+
+/// Read and parse a PNG file from disk
+fn read_png(path: &Path) -> Result<Png> {
+    let bytes = fs::read(path).with_context(|| format!("failed to read {}", path.display()))?;
+    Png::try_from(bytes.as_slice()).with_context(|| format!("failed to parse {}", path.display()))
+}
+
+/// Read a PNG file from disk that is going to be edited, keeping every byte
+/// that isn't deliberately changed
+fn read_png_raw(path: &Path) -> Result<Png> {
+    let bytes = fs::read(path).with_context(|| format!("failed to read {}", path.display()))?;
+    Png::from_bytes_raw(&bytes).with_context(|| format!("failed to parse {}", path.display()))
+}
+
+/// Stream a PNG to disk, chunk by chunk
+fn write_png(path: &Path, png: &Png) -> Result<()> {
+    let file =
+        File::create(path).with_context(|| format!("failed to create {}", path.display()))?;
+    let mut writer = PngWriter::new(BufWriter::new(file))?;
+    writer.write_png(png)?;
+    writer
+        .finish()
+        .with_context(|| format!("failed to write {}", path.display()))?;
+    Ok(())
+}
+
+/// Encodes a message into a PNG file and saves the result
+pub fn encode(args: EncodeArgs) -> Result<()> {
+    let chunk_type = ChunkType::from_str(&args.chunk_type)?;
+    let mut png = read_png_raw(&args.file)?;
+    let mut message = args.message.into_bytes();
+    if message == b"-" {
+        message.clear();
+        io::stdin()
+            .read_to_end(&mut message)
+            .context("failed to read message from stdin")?;
+    }
+    if args.compress {
+        message = payload::compress(&message);
+    }
+    let data = match &args.passphrase {
+        Some(passphrase) => crypto::seal(&message, passphrase)?,
+        None => message,
+    };
+    match args.method {
+        Method::Chunk => {
+            if args.key.is_some() {
+                bail!("--key only applies to --method lsb");
+            }
+            let chunks = match args.split {
+                Some(piece_length) => payload::split(&data, chunk_type, piece_length)?,
+                None => vec![Chunk::new(chunk_type, data)],
+            };
+            for chunk in chunks {
+                png.insert(chunk);
+            }
+        }
+        Method::Lsb => {
+            if args.split.is_some() {
+                bail!("--split only applies to --method chunk");
+            }
+            lsb::hide(
+                &mut png,
+                chunk_type,
+                &data,
+                &bit_order(args.key.as_deref())?,
+            )?;
+        }
+    }
+    write_png(args.output.as_deref().unwrap_or(&args.file), &png)
+}
+
+/// Searches for a message hidden in a PNG file and prints the message if one is found
+pub fn decode(args: DecodeArgs) -> Result<()> {
+    let chunk_type = ChunkType::from_str(&args.chunk_type)?;
+    let png = read_png(&args.file)?;
+    let (data, index) = match args.method {
+        Method::Chunk => {
+            let index = png
+                .chunks()
+                .iter()
+                .position(|chunk| *chunk.chunk_type() == chunk_type)
+                .with_context(|| format!("no {chunk_type} chunk in {}", args.file.display()))?;
+            let chunk = &png.chunks()[index];
+            let data = match payload::is_piece(chunk.data()) {
+                true => {
+                    let pieces: Vec<usize> = png
+                        .chunks()
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, chunk)| *chunk.chunk_type() == chunk_type)
+                        .map(|(index, _)| index)
+                        .collect();
+                    payload::join(pieces.iter().map(|&index| &png.chunks()[index])).map_err(
+                        |err| {
+                            let index = pieces[err.index()];
+                            err.at(png.chunk_offset(index), index)
+                        },
+                    )?
+                }
+                false => chunk.data().to_vec(),
+            };
+            (data, index)
+        }
+        Method::Lsb => {
+            let order = bit_order(args.key.as_deref())?;
+            let data = lsb::reveal(&png, chunk_type, &order)?;
+            let index = png
+                .chunks()
+                .iter()
+                .position(|chunk| *chunk.chunk_type() == ChunkType::IDAT)
+                .unwrap_or_default();
+            (data, index)
+        }
+    };
+    // errors in the message are reported at the chunk it was found in
+    let locate = |err: PngError| err.at(png.chunk_offset(index), index);
+    let mut message = match &args.passphrase {
+        Some(passphrase) => crypto::open(&data, passphrase).map_err(locate)?,
+        None => data,
+    };
+    if payload::is_compressed(&message) {
+        message = payload::decompress(&message, args.max_size).map_err(locate)?;
+    }
+    match args.output {
+        Some(output) => fs::write(&output, message)
+            .with_context(|| format!("failed to write {}", output.display()))?,
+        None => println!("{}", String::from_utf8(message)?),
+    }
+    Ok(())
+}
+
+/// The order the bits of a message go into the pixels, scattered if there
+/// is a key
+fn bit_order(key: Option<&str>) -> Result<BitOrder> {
+    Ok(match key {
+        Some(key) => BitOrder::from_key(key)?,
+        None => BitOrder::Sequential,
+    })
+}
+
+/// Removes a chunk from a PNG file and saves the result
+pub fn remove(args: RemoveArgs) -> Result<()> {
+    let chunk_type = ChunkType::from_str(&args.chunk_type)?;
+    let mut png = read_png_raw(&args.file)?;
+    if !args.all {
+        let chunk = png.remove_chunk(chunk_type)?;
+        write_png(&args.file, &png)?;
+        println!(
+            "removed {} chunk of {} bytes",
+            chunk.chunk_type(),
+            chunk.length()
+        );
+        return Ok(());
+    }
+    let removed = png.remove_all(chunk_type);
+    if removed.is_empty() {
+        bail!("no {chunk_type} chunks in {}", args.file.display());
+    }
+    write_png(&args.file, &png)?;
+    let length: u64 = removed.iter().map(|chunk| u64::from(chunk.length())).sum();
+    println!(
+        "removed {} {chunk_type} chunks of {length} bytes",
+        removed.len()
+    );
+    Ok(())
+}
+
+/// Prints all of the chunks in a PNG file
+pub fn print(args: PrintArgs) -> Result<()> {
+    let png = read_png(&args.file)?;
+    print!("{png}");
+    Ok(())
+}
+
+/// Reports every chunk ordering rule a PNG file breaks, and whether its
+/// pixels can be decoded
+pub fn check(args: CheckArgs) -> Result<()> {
+    let png = read_png(&args.file)?;
+    let violations = png.validate();
+    for violation in &violations {
+        println!("{violation}");
+    }
+    let image = png.image();
+    if let Err(err) = &image {
+        println!("{err}");
+    }
+    let problems = violations.len() + usize::from(image.is_err());
+    if problems > 0 {
+        bail!("{problems} problems found in {}", args.file.display());
+    }
+    println!("no problems found in {}", args.file.display());
+    Ok(())
+}
+
+/// Reports every problem in a damaged PNG file and optionally saves the
+/// chunks that could be salvaged
+pub fn recover(args: RecoverArgs) -> Result<()> {
+    let bytes =
+        fs::read(&args.file).with_context(|| format!("failed to read {}", args.file.display()))?;
+    let (png, diagnostics) = Png::recover(&bytes);
+    for diagnostic in &diagnostics {
+        println!("{diagnostic}");
+    }
+    println!(
+        "salvaged {} chunks with {} problems",
+        png.chunks().len(),
+        diagnostics.len()
+    );
+    if let Some(output) = args.output {
+        write_png(&output, &png)?;
+    }
+    Ok(())
+}
+
+/// Reads and edits the text metadata of a PNG file
+pub fn text(args: TextArgs) -> Result<()> {
+    match args.command {
+        TextCommand::List(args) => {
+            let png = read_png(&args.file)?;
+            for text in png.text_chunks()? {
+                println!("{} {text}", text.chunk_type());
+            }
+        }
+        TextCommand::Get(args) => {
+            let png = read_png(&args.file)?;
+            let text = png
+                .text(&args.keyword)?
+                .with_context(|| format!("no {} text in {}", args.keyword, args.file.display()))?;
+            println!("{}", text.text);
+        }
+        TextCommand::Set(args) => {
+            let mut png = read_png_raw(&args.file)?;
+            let international = args.international
+                || args.language.is_some()
+                || args.translated_keyword.is_some()
+                || args.text.chars().any(|c| u32::from(c) > 0xff);
+            let kind = match (international, args.compress) {
+                (true, compressed) => TextKind::International {
+                    compressed,
+                    language_tag: args.language.unwrap_or_default(),
+                    translated_keyword: args.translated_keyword.unwrap_or_default(),
+                },
+                (false, true) => TextKind::Compressed,
+                (false, false) => TextKind::Latin1,
+            };
+            png.set_text(TextChunk {
+                keyword: args.keyword,
+                text: args.text,
+                kind,
+            })?;
+            write_png(args.output.as_deref().unwrap_or(&args.file), &png)?;
+        }
+        TextCommand::Remove(args) => {
+            let mut png = read_png_raw(&args.file)?;
+            match png.remove_text(&args.keyword) {
+                0 => bail!("no {} text in {}", args.keyword, args.file.display()),
+                removed => println!("removed {removed} {} entries", args.keyword),
+            }
+            write_png(&args.file, &png)?;
+        }
+    }
+    Ok(())
+}
+
+/// Rewrites the image data of a PNG file with or without Adam7 interlacing
+pub fn interlace(args: InterlaceArgs) -> Result<()> {
+    let mut png = read_png_raw(&args.file)?;
+    let method = match args.remove {
+        true => InterlaceMethod::None,
+        false => InterlaceMethod::Adam7,
+    };
+    png.set_interlace_method(method)?;
+    write_png(args.output.as_deref().unwrap_or(&args.file), &png)
+}
+
+/// Prints how big each Adam7 pass of a PNG file is
+pub fn passes(args: PassesArgs) -> Result<()> {
+    let png = read_png(&args.file)?;
+    let header = png.header()?;
+    let index = png
+        .chunks()
+        .iter()
+        .position(|chunk| *chunk.chunk_type() == ChunkType::IHDR)
+        .unwrap_or_default();
+    let locate = |err: PngError| err.at(png.chunk_offset(index), index);
+    let data_length = header.data_length().map_err(locate)?;
+    if header.interlace_method == InterlaceMethod::None {
+        println!(
+            "{} is not interlaced, {data_length} bytes of image data",
+            args.file.display()
+        );
+        return Ok(());
+    }
+    for (number, size) in adam7::pass_sizes(&header)
+        .map_err(locate)?
+        .iter()
+        .enumerate()
+    {
+        println!("pass {}: {size}", number + 1);
+    }
+    println!("total: {data_length} bytes");
+    Ok(())
+}
+
+/// Prints how many bytes can be hidden in the pixels of a PNG file
+pub fn capacity(args: CapacityArgs) -> Result<()> {
+    let png = read_png(&args.file)?;
+    let capacity = lsb::capacity(&png)?;
+    println!(
+        "{capacity} bytes can be hidden in the pixels of {}",
+        args.file.display()
+    );
+    println!(
+        "{} bytes with --passphrase",
+        capacity.saturating_sub(crypto::OVERHEAD as u64)
+    );
+    Ok(())
+}
+
+/// Re-encodes the image data of a PNG file in whichever way is smallest and
+/// saves the result
+pub fn optimize(args: OptimizeArgs) -> Result<()> {
+    let png = read_png(&args.file)?;
+    let keep = parse_chunk_types(&args.keep)?;
+    let mut options = OptimizeOptions {
+        strip: (args.strip || !keep.is_empty())
+            .then(|| Policy::all_ancillary().keeping_types(keep)),
+        ..Default::default()
+    };
+    if !args.levels.is_empty() {
+        options.levels = args.levels;
+    }
+    let (optimized, report) = optimize::optimize(&png, &options)?;
+    write_png(args.output.as_deref().unwrap_or(&args.file), &optimized)?;
+    println!("{report}");
+    Ok(())
+}
+
+/// Removes metadata from a PNG file, or lists what would be removed
+pub fn strip(args: StripArgs) -> Result<()> {
+    let mut png = read_png_raw(&args.file)?;
+    let policy = match args.profile {
+        StripProfile::All => Policy::all_ancillary(),
+        StripProfile::Color => Policy::keep_color_management(),
+    }
+    .keeping_types(parse_chunk_types(&args.keep)?)
+    .keeping_keywords(args.keep_text);
+    let (removals, verb) = match args.dry_run {
+        true => (strip::removals(&png, &policy), "would remove"),
+        false => (png.strip(&policy), "removed"),
+    };
+    if removals.is_empty() {
+        println!("nothing to remove from {}", args.file.display());
+        return Ok(());
+    }
+    for removal in &removals {
+        println!("{verb} {removal}");
+    }
+    let saved: u64 = removals.iter().map(|removal| removal.length).sum();
+    match args.dry_run {
+        true => println!("would save {saved} bytes"),
+        false => {
+            write_png(args.output.as_deref().unwrap_or(&args.file), &png)?;
+            println!("saved {saved} bytes");
+        }
+    }
+    Ok(())
+}
+
+/// Prints the frames of an animated PNG file, optionally writing each one
+/// to its own PNG file
+pub fn frames(args: FramesArgs) -> Result<()> {
+    let png = read_png(&args.file)?;
+    let animation = png.animation()?;
+    println!("{}", animation.control);
+    if !animation.default_image_is_frame {
+        println!("the default image is not part of the animation");
+    }
+    for (number, frame) in animation.frames.iter().enumerate() {
+        println!("frame {number}: {}", frame.control);
+    }
+    let Some(directory) = args.extract else {
+        return Ok(());
+    };
+    fs::create_dir_all(&directory)
+        .with_context(|| format!("failed to create {}", directory.display()))?;
+    let stem = args
+        .file
+        .file_stem()
+        .map_or("frame".into(), |stem| stem.to_string_lossy());
+    for (number, image) in animation.render(&png)?.iter().enumerate() {
+        let path = directory.join(format!("{stem}-{number:03}.png"));
+        write_png(&path, &image.to_png()?)?;
+        println!("wrote {}", path.display());
+    }
+    Ok(())
+}
+
+/// Prints every chunk of a PNG file, as JSON or one line per chunk
+pub fn dump(args: DumpArgs) -> Result<()> {
+    let png = read_png_raw(&args.file)?;
+    let dump = dump::dump(&png);
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&dump)?);
+        return Ok(());
+    }
+    for chunk in &dump.chunks {
+        println!(
+            "chunk {} {} at byte {}, {} bytes, crc {:08x}",
+            chunk.index, chunk.chunk_type, chunk.offset, chunk.length, chunk.crc
+        );
+    }
+    if !png.trailing_bytes().is_empty() {
+        println!(
+            "{} trailing bytes at byte {}",
+            png.trailing_bytes().len(),
+            png.chunk_offset(png.chunks().len())
+        );
+    }
+    Ok(())
+}
+
+/// Writes a PNG file from a JSON dump, warning about any chunk ordering
+/// rules it breaks
+pub fn build(args: BuildArgs) -> Result<()> {
+    let mut json = String::new();
+    match args.json.as_os_str() == "-" {
+        true => io::stdin()
+            .read_to_string(&mut json)
+            .context("failed to read JSON from stdin")?,
+        false => File::open(&args.json)
+            .and_then(|mut file| file.read_to_string(&mut json))
+            .with_context(|| format!("failed to read {}", args.json.display()))?,
+    };
+    let dump: Dump = serde_json::from_str(&json)
+        .with_context(|| format!("failed to parse {}", args.json.display()))?;
+    let png = dump::build(&dump)?;
+    for violation in png.validate() {
+        eprintln!("warning: {violation}");
+    }
+    write_png(&args.output, &png)
+}
+
+/// Prints a table of every chunk in a PNG file, with the property bits of
+/// its type, whether its CRC is right and what it says
+pub fn inspect(args: InspectArgs) -> Result<()> {
+    let png = read_png_raw(&args.file)?;
+    let yes_no = |bit: bool| if bit { "yes" } else { "no" };
+    let limit = (args.hex_limit > 0).then_some(args.hex_limit);
+    let mut problems = 0;
+    println!(
+        "{:>5} {:>10} {:>10}  type  crit pub  rsv  safe  crc",
+        "index", "offset", "length"
+    );
+    for (report, chunk) in inspect::inspect(&png).iter().zip(png.chunks()) {
+        let chunk_type = &report.chunk_type;
+        let summary = match &report.summary {
+            Ok(summary) => summary.clone().unwrap_or_default(),
+            Err(err) => {
+                problems += 1;
+                err.to_string()
+            }
+        };
+        if report.crc != CrcStatus::Ok {
+            problems += 1;
+        }
+        let row = format!(
+            "{:>5} {:>10} {:>10}  {chunk_type}  {:<4} {:<4} {:<4} {:<4}  {}  {summary}",
+            report.index,
+            report.offset,
+            report.length,
+            yes_no(chunk_type.is_critical()),
+            yes_no(chunk_type.is_public()),
+            yes_no(chunk_type.is_reserved_bit_valid()),
+            yes_no(chunk_type.is_safe_to_copy()),
+            report.crc,
+        );
+        println!("{}", row.trim_end());
+        if args.hex {
+            print!("{}", inspect::hex_dump(chunk.data(), limit));
+        }
+    }
+    if let Some(err) = inspect::tail_error(&png) {
+        problems += 1;
+        println!(
+            "{} unparsed bytes from byte {}: {err}",
+            png.trailing_bytes().len(),
+            err.offset()
+        );
+    } else if !png.trailing_bytes().is_empty() {
+        println!(
+            "{} trailing bytes at byte {}",
+            png.trailing_bytes().len(),
+            png.chunk_offset(png.chunks().len())
+        );
+    }
+    if problems > 0 {
+        bail!("{problems} problems found in {}", args.file.display());
+    }
+    Ok(())
+}
+
+/// Prints the chunk differences between two PNG files, as text or JSON,
+/// and fails if there are any
+pub fn diff(args: DiffArgs) -> Result<()> {
+    let old = read_png_raw(&args.old)?;
+    let new = read_png_raw(&args.new)?;
+    let diff = diff::diff(&old, &new, &parse_chunk_types(&args.ignore)?);
+    match args.json {
+        true => println!("{}", serde_json::to_string_pretty(&diff)?),
+        false => print!("{diff}"),
+    }
+    if !diff.is_empty() {
+        bail!("{} and {} differ", args.old.display(), args.new.display());
+    }
+    Ok(())
+}
+
+/// Prints what the registry knows about a chunk type and what the case of
+/// each of its letters means
+pub fn explain(args: ExplainArgs) -> Result<()> {
+    let chunk_type = ChunkType::from_str(&args.chunk_type)?;
+    let info = registry::lookup(&chunk_type);
+    match info {
+        Some(info) => {
+            let ordering = match info.constraints {
+                [] => "anywhere between IHDR and IEND".to_string(),
+                constraints => constraints
+                    .iter()
+                    .map(|constraint| constraint.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            };
+            println!("{chunk_type}: {}", info.name);
+            match info.section {
+                Some(section) => {
+                    println!("  specification: {}, section {section}", info.specification)
+                }
+                None => println!("  specification: {}", info.specification),
+            }
+            println!("  multiplicity:  {}", info.multiplicity);
+            println!("  ordering:      {ordering}");
+        }
+        None => println!("{chunk_type}: not a registered chunk type"),
+    }
+    let bytes = chunk_type.bytes();
+    let bit = |index: usize, set: &str, unset: &str| {
+        let letter = char::from(bytes[index]);
+        match letter.is_ascii_uppercase() {
+            true => format!("{unset} ({letter} is uppercase)"),
+            false => format!("{set} ({letter} is lowercase)"),
+        }
+    };
+    println!("  {}", bit(0, "ancillary", "critical"));
+    println!("  {}", bit(1, "private", "public"));
+    println!(
+        "  {}",
+        bit(2, "reserved bit set, invalid", "reserved bit clear")
+    );
+    println!("  {}", bit(3, "safe to copy", "unsafe to copy"));
+    if let Some(info) = info {
+        println!("  {}", info.description);
+    }
+    Ok(())
+}
+
+/// Parse chunk types given on the command line
+fn parse_chunk_types(chunk_types: &[String]) -> Result<Vec<ChunkType>> {
+    Ok(chunk_types
+        .iter()
+        .map(|chunk_type| ChunkType::from_str(chunk_type))
+        .collect::<Result<Vec<ChunkType>, PngError>>()?)
+}
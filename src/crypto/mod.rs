@@ -0,0 +1,193 @@
+//! Passphrase based encryption of hidden messages.
+//!
+//! A key is derived from the passphrase with Argon2id, a memory-hard key
+//! derivation function, and the message is sealed with XChaCha20-Poly1305 so
+//! that a wrong passphrase or any change to the sealed bytes is detected.
+//! Everything needed to open the message again, apart from the passphrase,
+//! is stored in front of the ciphertext:
+//!
+//! | Field                   | Size     |
+//! |-------------------------|----------|
+//! | Format version (1)      | 1 byte   |
+//! | Argon2 memory cost, KiB | 4 bytes  |
+//! | Argon2 iterations       | 4 bytes  |
+//! | Argon2 parallelism      | 4 bytes  |
+//! | Salt                    | 16 bytes |
+//! | Nonce                   | 24 bytes |
+//! | Ciphertext and tag      | rest     |
+//!
+//! The header is authenticated along with the message.
+
+#[cfg(test)]
+mod unit_tests;
+
+use argon2::{Algorithm, Argon2, Params, Version};
+use chacha20poly1305::{
+    aead::{rand_core::RngCore, Aead, AeadCore, KeyInit, OsRng, Payload},
+    XChaCha20Poly1305, XNonce,
+};
+
+use crate::error::PngError;
+
+const VERSION: u8 = 1;
+const SALT_LENGTH: usize = 16;
+const NONCE_LENGTH: usize = 24;
+const HEADER_LENGTH: usize = 1 + 12 + SALT_LENGTH + NONCE_LENGTH;
+/// Number of bytes [`seal`] adds to a message
+pub const OVERHEAD: usize = HEADER_LENGTH + 16;
+/// Refuse to open messages that ask for more than 1 GiB of memory
+const MAX_MEMORY_COST: u32 = 1024 * 1024;
+/// Refuse to open messages that ask for more passes than this
+const MAX_ITERATIONS: u32 = 64;
+/// Refuse to open messages that ask for more lanes than this
+const MAX_PARALLELISM: u32 = 64;
+
+/// Cost settings for deriving a key from a passphrase.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct KdfParams {
+    /// Memory used, in KiB
+    pub memory_cost: u32,
+    /// Number of passes over the memory
+    pub iterations: u32,
+    /// Number of lanes
+    pub parallelism: u32,
+}
+
+impl Default for KdfParams {
+    /// The Argon2id settings recommended by OWASP
+    fn default() -> Self {
+        KdfParams {
+            memory_cost: Params::DEFAULT_M_COST,
+            iterations: Params::DEFAULT_T_COST,
+            parallelism: Params::DEFAULT_P_COST,
+        }
+    }
+}
+
+/// Encrypt a message with a key derived from `passphrase`
+pub fn seal(message: &[u8], passphrase: &str) -> Result<Vec<u8>, PngError> {
+    seal_with_params(message, passphrase, KdfParams::default())
+}
+
+/// Encrypt a message with a key derived from `passphrase` using the given
+/// key derivation costs
+pub fn seal_with_params(
+    message: &[u8],
+    passphrase: &str,
+    params: KdfParams,
+) -> Result<Vec<u8>, PngError> {
+    let mut salt = [0; SALT_LENGTH];
+    OsRng.fill_bytes(&mut salt);
+    let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+
+    let mut sealed = Vec::with_capacity(HEADER_LENGTH + message.len() + 16);
+    sealed.push(VERSION);
+    sealed.extend(params.memory_cost.to_be_bytes());
+    sealed.extend(params.iterations.to_be_bytes());
+    sealed.extend(params.parallelism.to_be_bytes());
+    sealed.extend(salt);
+    sealed.extend(nonce);
+
+    let cipher = derive_cipher(passphrase, &salt, params)?;
+    let ciphertext = cipher
+        .encrypt(
+            &nonce,
+            Payload {
+                msg: message,
+                aad: &sealed,
+            },
+        )
+        .map_err(|_| invalid("encryption failed"))?;
+    sealed.extend(ciphertext);
+    Ok(sealed)
+}
+
+/// Decrypt a message sealed by [`seal`]. Fails with
+/// [`PngError::AuthenticationFailed`] if the passphrase is wrong or the
+/// sealed bytes have been changed.
+pub fn open(sealed: &[u8], passphrase: &str) -> Result<Vec<u8>, PngError> {
+    if sealed.len() < OVERHEAD {
+        return Err(invalid(format!(
+            "{} bytes is too short to be an encrypted message",
+            sealed.len()
+        )));
+    }
+    let (header, ciphertext) = sealed.split_at(HEADER_LENGTH);
+    if header[0] != VERSION {
+        return Err(invalid(format!("unknown format version {}", header[0])));
+    }
+    let word = |at: usize| {
+        u32::from_be_bytes([header[at], header[at + 1], header[at + 2], header[at + 3]])
+    };
+    let params = KdfParams {
+        memory_cost: word(1),
+        iterations: word(5),
+        parallelism: word(9),
+    };
+    if params.memory_cost > MAX_MEMORY_COST {
+        return Err(invalid(format!(
+            "memory cost of {} KiB is more than the limit of {MAX_MEMORY_COST} KiB",
+            params.memory_cost
+        )));
+    }
+    if params.iterations > MAX_ITERATIONS {
+        return Err(invalid(format!(
+            "{} iterations is more than the limit of {MAX_ITERATIONS}",
+            params.iterations
+        )));
+    }
+    if params.parallelism > MAX_PARALLELISM {
+        return Err(invalid(format!(
+            "parallelism of {} is more than the limit of {MAX_PARALLELISM}",
+            params.parallelism
+        )));
+    }
+    let salt = &header[13..13 + SALT_LENGTH];
+    let nonce = XNonce::from_slice(&header[13 + SALT_LENGTH..]);
+
+    derive_cipher(passphrase, salt, params)?
+        .decrypt(
+            nonce,
+            Payload {
+                msg: ciphertext,
+                aad: header,
+            },
+        )
+        .map_err(|_| PngError::AuthenticationFailed {
+            offset: 0,
+            index: 0,
+        })
+}
+
+/// Stretch a passphrase into a 32 byte key with Argon2id
+pub fn derive_key(passphrase: &str, salt: &[u8], params: KdfParams) -> Result<[u8; 32], PngError> {
+    let params = Params::new(
+        params.memory_cost,
+        params.iterations,
+        params.parallelism,
+        Some(32),
+    )
+    .map_err(|err| invalid(format!("invalid key derivation settings: {err}")))?;
+    let mut key = [0; 32];
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|err| invalid(format!("key derivation failed: {err}")))?;
+    Ok(key)
+}
+
+fn derive_cipher(
+    passphrase: &str,
+    salt: &[u8],
+    params: KdfParams,
+) -> Result<XChaCha20Poly1305, PngError> {
+    let key = derive_key(passphrase, salt, params)?;
+    Ok(XChaCha20Poly1305::new(&key.into()))
+}
+
+fn invalid(reason: impl Into<String>) -> PngError {
+    PngError::InvalidCiphertext {
+        reason: reason.into(),
+        offset: 0,
+        index: 0,
+    }
+}
@@ -0,0 +1,96 @@
+use super::*;
+
+// cheap settings so the tests run quickly
+const TEST_PARAMS: KdfParams = KdfParams {
+    memory_cost: 64,
+    iterations: 1,
+    parallelism: 1,
+};
+
+fn testing_sealed() -> Vec<u8> {
+    seal_with_params(b"meet me at midnight", "correct horse", TEST_PARAMS).unwrap()
+}
+
+#[test]
+fn test_round_trip() {
+    let sealed = testing_sealed();
+    assert_eq!(sealed.len(), HEADER_LENGTH + 19 + 16);
+    assert_eq!(
+        open(&sealed, "correct horse").unwrap(),
+        b"meet me at midnight"
+    );
+}
+
+#[test]
+fn test_not_plain_text() {
+    let sealed = testing_sealed();
+    assert!(!sealed.windows(8).any(|window| window == b"midnight"));
+    assert_ne!(sealed, testing_sealed(), "fresh salt and nonce every time");
+}
+
+#[test]
+fn test_wrong_passphrase() {
+    let sealed = testing_sealed();
+    assert!(matches!(
+        open(&sealed, "battery staple"),
+        Err(PngError::AuthenticationFailed { .. })
+    ));
+}
+
+#[test]
+fn test_tampering_detected() {
+    let sealed = testing_sealed();
+    // flipping any bit of the salt, nonce or ciphertext must be caught
+    for position in [14, 13 + SALT_LENGTH, HEADER_LENGTH, sealed.len() - 1] {
+        let mut tampered = sealed.clone();
+        tampered[position] ^= 1;
+        assert!(
+            matches!(
+                open(&tampered, "correct horse"),
+                Err(PngError::AuthenticationFailed { .. })
+            ),
+            "tampered at {position}"
+        );
+    }
+}
+
+#[test]
+fn test_malformed() {
+    let sealed = testing_sealed();
+    assert!(matches!(
+        open(&sealed[..HEADER_LENGTH], "correct horse"),
+        Err(PngError::InvalidCiphertext { .. })
+    ));
+    let mut unknown_version = sealed.clone();
+    unknown_version[0] = 2;
+    assert!(matches!(
+        open(&unknown_version, "correct horse"),
+        Err(PngError::InvalidCiphertext { .. })
+    ));
+    let mut expensive = sealed;
+    expensive[1] = 0xff;
+    assert!(matches!(
+        open(&expensive, "correct horse"),
+        Err(PngError::InvalidCiphertext { .. })
+    ));
+}
+
+#[test]
+fn test_too_many_iterations() {
+    let mut expensive = testing_sealed();
+    expensive[5..9].copy_from_slice(&u32::MAX.to_be_bytes());
+    assert!(matches!(
+        open(&expensive, "correct horse"),
+        Err(PngError::InvalidCiphertext { .. })
+    ));
+}
+
+#[test]
+fn test_too_much_parallelism() {
+    let mut expensive = testing_sealed();
+    expensive[9..13].copy_from_slice(&(MAX_PARALLELISM + 1).to_be_bytes());
+    assert!(matches!(
+        open(&expensive, "correct horse"),
+        Err(PngError::InvalidCiphertext { .. })
+    ));
+}
@@ -0,0 +1,403 @@
+//! Comparing the chunks of two pngs. Chunks are paired up by type, text
+//! chunks also by keyword, and chunks that share both are paired in the
+//! order they appear. What is left over has been added or removed. Paired
+//! chunks have moved if they are out of the longest run that keeps its
+//! order in both files, and have changed if their data differs.
+
+#[cfg(test)]
+mod unit_tests;
+
+use std::{collections::HashMap, fmt::Display};
+
+use serde::Serialize;
+
+use crate::{
+    chunk::Chunk,
+    chunk_type::ChunkType,
+    image_header::{ImageHeader, InterlaceMethod},
+    png::Png,
+    text::{TextChunk, TextKind},
+};
+
+/// Every difference between the chunks of two pngs.
+#[derive(Serialize, PartialEq, Eq, Debug)]
+pub struct Diff {
+    /// Differences in the order of the new file, with removed chunks after
+    /// the chunk that came before them in the old one
+    pub chunks: Vec<ChunkChange>,
+    /// Which bytes after the last chunk differ
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trailing: Option<ByteRange>,
+}
+
+impl Diff {
+    /// Whether the two pngs are the same
+    pub fn is_empty(&self) -> bool {
+        self.chunks.is_empty() && self.trailing.is_none()
+    }
+}
+
+impl Display for Diff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for change in &self.chunks {
+            writeln!(f, "{change}")?;
+        }
+        if let Some(trailing) = &self.trailing {
+            writeln!(f, "trailing bytes changed: {trailing}")?;
+        }
+        Ok(())
+    }
+}
+
+/// A chunk that is only in one file, or that is in a different place or
+/// has different data in the other.
+#[derive(Serialize, PartialEq, Eq, Debug)]
+pub struct ChunkChange {
+    pub kind: ChangeKind,
+    #[serde(rename = "type")]
+    pub chunk_type: String,
+    /// Keyword of a text chunk
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub keyword: Option<String>,
+    /// Index of the chunk in the old file
+    pub old_index: Option<usize>,
+    /// Index of the chunk in the new file
+    pub new_index: Option<usize>,
+    /// Whether the chunk is out of order compared to the other chunks
+    pub moved: bool,
+    /// How the data of a changed chunk differs
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub difference: Option<Difference>,
+}
+
+impl Display for ChunkChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match &self.keyword {
+            Some(keyword) => format!("{} ({keyword})", self.chunk_type),
+            None => self.chunk_type.clone(),
+        };
+        let index = |index: Option<usize>| index.map_or("?".to_string(), |index| index.to_string());
+        let (old, new) = (index(self.old_index), index(self.new_index));
+        match self.kind {
+            ChangeKind::Added => write!(f, "+ chunk {new} {name} added")?,
+            ChangeKind::Removed => write!(f, "- chunk {old} {name} removed")?,
+            ChangeKind::Moved => write!(f, "~ chunk {old} {name} moved to chunk {new}")?,
+            ChangeKind::Changed if self.moved => {
+                write!(f, "* chunk {old} {name} moved to chunk {new} and changed")?
+            }
+            ChangeKind::Changed => write!(f, "* chunk {new} {name} changed")?,
+        }
+        match &self.difference {
+            Some(Difference::Fields(fields)) => fields
+                .iter()
+                .try_for_each(|field| write!(f, "\n    {field}")),
+            Some(Difference::Bytes(range)) => write!(f, "\n    {range}"),
+            None => Ok(()),
+        }
+    }
+}
+
+#[derive(Serialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeKind {
+    /// Only in the new file
+    Added,
+    /// Only in the old file
+    Removed,
+    /// In both with the same data, but out of order
+    Moved,
+    /// In both with different data
+    Changed,
+}
+
+/// How the data of a chunk differs: field by field for the chunk types
+/// that are decoded here, otherwise as the range of bytes that differ.
+#[derive(Serialize, PartialEq, Eq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum Difference {
+    Fields(Vec<FieldChange>),
+    Bytes(ByteRange),
+}
+
+/// A decoded field with a different value in the new file.
+#[derive(Serialize, PartialEq, Eq, Debug)]
+pub struct FieldChange {
+    pub field: String,
+    pub old: String,
+    pub new: String,
+}
+
+impl Display for FieldChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {} -> {}", self.field, self.old, self.new)
+    }
+}
+
+/// The bytes between the longest common prefix and the longest common
+/// suffix of two byte strings. `start` is where they first differ and the
+/// ends are where each stops differing.
+#[derive(Serialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ByteRange {
+    pub start: usize,
+    pub old_end: usize,
+    pub new_end: usize,
+    pub old_length: usize,
+    pub new_length: usize,
+}
+
+impl ByteRange {
+    /// The range that differs, or None if the bytes are the same
+    pub fn between(old: &[u8], new: &[u8]) -> Option<ByteRange> {
+        if old == new {
+            return None;
+        }
+        let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+        let suffix = old[prefix..]
+            .iter()
+            .rev()
+            .zip(new[prefix..].iter().rev())
+            .take_while(|(a, b)| a == b)
+            .count();
+        Some(ByteRange {
+            start: prefix,
+            old_end: old.len() - suffix,
+            new_end: new.len() - suffix,
+            old_length: old.len(),
+            new_length: new.len(),
+        })
+    }
+}
+
+impl Display for ByteRange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "bytes {}..{} of {} became {}..{} of {}",
+            self.start, self.old_end, self.old_length, self.start, self.new_end, self.new_length
+        )
+    }
+}
+
+/// Compare the chunks of `old` and `new`, leaving out chunks of the
+/// `ignore` types
+pub fn diff(old: &Png, new: &Png, ignore: &[ChunkType]) -> Diff {
+    let keep = |chunk: &Chunk| !ignore.contains(chunk.chunk_type());
+    let old_chunks: Vec<(usize, &Chunk)> = old
+        .chunks()
+        .iter()
+        .enumerate()
+        .filter(|(_, chunk)| keep(chunk))
+        .collect();
+    let new_chunks: Vec<(usize, &Chunk)> = new
+        .chunks()
+        .iter()
+        .enumerate()
+        .filter(|(_, chunk)| keep(chunk))
+        .collect();
+
+    // the nth old chunk with a key goes with the nth new one
+    let mut waiting: HashMap<Key, Vec<usize>> = HashMap::new();
+    for (position, (_, chunk)) in new_chunks.iter().enumerate().rev() {
+        waiting.entry(key(chunk)).or_default().push(position);
+    }
+    let mut pairs = Vec::new();
+    let mut removed = Vec::new();
+    for (position, (_, chunk)) in old_chunks.iter().enumerate() {
+        match waiting.get_mut(&key(chunk)).and_then(Vec::pop) {
+            Some(new_position) => pairs.push((position, new_position)),
+            None => removed.push(position),
+        }
+    }
+    let in_order = longest_increasing(&pairs.iter().map(|&(_, new)| new).collect::<Vec<_>>());
+
+    let change = |kind, chunk: &Chunk, old_index, new_index| ChunkChange {
+        kind,
+        chunk_type: chunk.chunk_type().to_string(),
+        keyword: TextChunk::keyword_of(chunk),
+        old_index,
+        new_index,
+        moved: false,
+        difference: None,
+    };
+    // chunks in the new file are ordered by where they are, and removed
+    // chunks go after whatever came before them in the old file
+    let mut changes: Vec<((usize, usize), ChunkChange)> = Vec::new();
+    for position in removed {
+        let (index, chunk) = old_chunks[position];
+        let after = pairs
+            .iter()
+            .rev()
+            .find(|&&(old_position, _)| old_position < position)
+            .map_or(0, |&(_, new_position)| new_chunks[new_position].0 + 1);
+        changes.push((
+            (after, index + 1),
+            change(ChangeKind::Removed, chunk, Some(index), None),
+        ));
+    }
+    let mut paired = vec![false; new_chunks.len()];
+    for (number, &(old_position, new_position)) in pairs.iter().enumerate() {
+        paired[new_position] = true;
+        let (old_index, old_chunk) = old_chunks[old_position];
+        let (new_index, new_chunk) = new_chunks[new_position];
+        let moved = !in_order[number];
+        let difference = compare(old_chunk, new_chunk);
+        let kind = match (&difference, moved) {
+            (Some(_), _) => ChangeKind::Changed,
+            (None, true) => ChangeKind::Moved,
+            (None, false) => continue,
+        };
+        changes.push((
+            (new_index + 1, 0),
+            ChunkChange {
+                moved,
+                difference,
+                ..change(kind, new_chunk, Some(old_index), Some(new_index))
+            },
+        ));
+    }
+    for (position, &(index, chunk)) in new_chunks.iter().enumerate() {
+        if !paired[position] {
+            changes.push((
+                (index + 1, 0),
+                change(ChangeKind::Added, chunk, None, Some(index)),
+            ));
+        }
+    }
+    changes.sort_by_key(|(position, _)| *position);
+
+    Diff {
+        chunks: changes.into_iter().map(|(_, change)| change).collect(),
+        trailing: ByteRange::between(old.trailing_bytes(), new.trailing_bytes()),
+    }
+}
+
+/// What a chunk is paired up by
+#[derive(Hash, PartialEq, Eq)]
+struct Key([u8; 4], Option<String>);
+
+fn key(chunk: &Chunk) -> Key {
+    Key(chunk.chunk_type().bytes(), TextChunk::keyword_of(chunk))
+}
+
+/// How the data of two chunks of the same type differs, if it does
+fn compare(old: &Chunk, new: &Chunk) -> Option<Difference> {
+    let range = ByteRange::between(old.data(), new.data())?;
+    let fields = match *old.chunk_type() {
+        ChunkType::IHDR => ImageHeader::try_from(old.data())
+            .ok()
+            .zip(ImageHeader::try_from(new.data()).ok())
+            .map(|(old, new)| header_fields(&old, &new)),
+        ChunkType::tEXt | ChunkType::zTXt | ChunkType::iTXt => TextChunk::try_from(old)
+            .ok()
+            .zip(TextChunk::try_from(new).ok())
+            .map(|(old, new)| text_fields(&old, &new)),
+        _ => None,
+    };
+    match fields {
+        // the fields can be the same when the bytes aren't, e.g. if text
+        // was compressed differently
+        Some(fields) if !fields.is_empty() => Some(Difference::Fields(fields)),
+        _ => Some(Difference::Bytes(range)),
+    }
+}
+
+fn header_fields(old: &ImageHeader, new: &ImageHeader) -> Vec<FieldChange> {
+    let interlace = |method: InterlaceMethod| match method {
+        InterlaceMethod::None => "none",
+        InterlaceMethod::Adam7 => "adam7",
+    };
+    let mut fields = Vec::new();
+    push_field(&mut fields, "width", old.width, new.width);
+    push_field(&mut fields, "height", old.height, new.height);
+    push_field(&mut fields, "bit depth", old.bit_depth, new.bit_depth);
+    push_field(&mut fields, "color type", old.color_type, new.color_type);
+    push_field(
+        &mut fields,
+        "compression method",
+        old.compression_method,
+        new.compression_method,
+    );
+    push_field(
+        &mut fields,
+        "filter method",
+        old.filter_method,
+        new.filter_method,
+    );
+    push_field(
+        &mut fields,
+        "interlace method",
+        interlace(old.interlace_method),
+        interlace(new.interlace_method),
+    );
+    fields
+}
+
+fn text_fields(old: &TextChunk, new: &TextChunk) -> Vec<FieldChange> {
+    let mut fields = Vec::new();
+    push_field(
+        &mut fields,
+        "text",
+        format!("{:?}", old.text),
+        format!("{:?}", new.text),
+    );
+    if let (
+        TextKind::International {
+            compressed: old_compressed,
+            language_tag: old_tag,
+            translated_keyword: old_keyword,
+        },
+        TextKind::International {
+            compressed: new_compressed,
+            language_tag: new_tag,
+            translated_keyword: new_keyword,
+        },
+    ) = (&old.kind, &new.kind)
+    {
+        push_field(&mut fields, "compressed", old_compressed, new_compressed);
+        push_field(
+            &mut fields,
+            "language tag",
+            format!("{old_tag:?}"),
+            format!("{new_tag:?}"),
+        );
+        push_field(
+            &mut fields,
+            "translated keyword",
+            format!("{old_keyword:?}"),
+            format!("{new_keyword:?}"),
+        );
+    }
+    fields
+}
+
+fn push_field<T: Display + PartialEq>(fields: &mut Vec<FieldChange>, field: &str, old: T, new: T) {
+    if old != new {
+        fields.push(FieldChange {
+            field: field.to_string(),
+            old: old.to_string(),
+            new: new.to_string(),
+        });
+    }
+}
+
+/// Mark the values that make up a longest strictly increasing subsequence
+fn longest_increasing(values: &[usize]) -> Vec<bool> {
+    // length of the longest run ending at each value, and the value before
+    let mut lengths = vec![1; values.len()];
+    let mut previous = vec![None; values.len()];
+    for i in 0..values.len() {
+        for j in 0..i {
+            if values[j] < values[i] && lengths[j] + 1 > lengths[i] {
+                lengths[i] = lengths[j] + 1;
+                previous[i] = Some(j);
+            }
+        }
+    }
+    let mut marked = vec![false; values.len()];
+    let mut at = (0..values.len()).max_by_key(|&i| (lengths[i], std::cmp::Reverse(i)));
+    while let Some(i) = at {
+        marked[i] = true;
+        at = previous[i];
+    }
+    marked
+}
@@ -0,0 +1,180 @@
+use super::*;
+use crate::test_utils::chunk;
+
+fn header(width: u8) -> Chunk {
+    chunk("IHDR", &[0, 0, 0, width, 0, 0, 0, 1, 8, 0, 0, 0, 0])
+}
+
+fn testing_png(chunks: Vec<Chunk>) -> Png {
+    let mut all = vec![header(1)];
+    all.extend(chunks);
+    all.push(chunk("IDAT", &[1, 2, 3, 4, 5]));
+    all.push(chunk("IEND", &[]));
+    Png::from_chunks(all)
+}
+
+fn old_png() -> Png {
+    testing_png(vec![
+        chunk("sRGB", &[0]),
+        chunk("gAMA", &[0, 0, 177, 143]),
+        chunk("tEXt", b"Title\0Dice"),
+        chunk("tEXt", b"Author\0Someone"),
+    ])
+}
+
+fn lines(diff: &Diff) -> Vec<String> {
+    diff.to_string().lines().map(str::to_string).collect()
+}
+
+#[test]
+fn test_same() {
+    let diff = diff(&old_png(), &old_png(), &[]);
+    assert!(diff.is_empty());
+    assert_eq!(diff.to_string(), "");
+}
+
+#[test]
+fn test_added_and_removed() {
+    let new = testing_png(vec![
+        chunk("sRGB", &[0]),
+        chunk("tEXt", b"Title\0Dice"),
+        chunk("tEXt", b"Author\0Someone"),
+        chunk("tIME", &[7, 234, 10, 16, 9, 5, 0]),
+    ]);
+    let diff = diff(&old_png(), &new, &[]);
+    assert_eq!(
+        lines(&diff),
+        ["- chunk 2 gAMA removed", "+ chunk 4 tIME added"]
+    );
+    assert_eq!(diff.chunks[0].old_index, Some(2));
+    assert_eq!(diff.chunks[0].new_index, None);
+}
+
+#[test]
+fn test_text_field_change() {
+    let new = testing_png(vec![
+        chunk("sRGB", &[0]),
+        chunk("gAMA", &[0, 0, 177, 143]),
+        chunk("tEXt", b"Title\0Two dice"),
+        chunk("tEXt", b"Author\0Someone"),
+    ]);
+    let diff = diff(&old_png(), &new, &[]);
+    assert_eq!(
+        lines(&diff),
+        [
+            "* chunk 3 tEXt (Title) changed",
+            r#"    text: "Dice" -> "Two dice""#
+        ]
+    );
+    assert_eq!(diff.chunks[0].keyword.as_deref(), Some("Title"));
+}
+
+#[test]
+fn test_header_field_change() {
+    let mut chunks: Vec<Chunk> = old_png()
+        .chunks()
+        .iter()
+        .map(|chunk| Chunk::new(*chunk.chunk_type(), chunk.data().to_vec()))
+        .collect();
+    chunks[0] = header(2);
+    let diff = diff(&old_png(), &Png::from_chunks(chunks), &[]);
+    assert_eq!(
+        lines(&diff),
+        ["* chunk 0 IHDR changed", "    width: 1 -> 2"]
+    );
+}
+
+#[test]
+fn test_byte_range_change() {
+    let mut chunks: Vec<Chunk> = old_png()
+        .chunks()
+        .iter()
+        .map(|chunk| Chunk::new(*chunk.chunk_type(), chunk.data().to_vec()))
+        .collect();
+    chunks[5] = chunk("IDAT", &[1, 2, 9, 9, 9, 4, 5]);
+    let diff = diff(&old_png(), &Png::from_chunks(chunks), &[]);
+    assert_eq!(
+        lines(&diff),
+        [
+            "* chunk 5 IDAT changed",
+            "    bytes 2..3 of 5 became 2..5 of 7"
+        ]
+    );
+}
+
+#[test]
+fn test_moved() {
+    let new = testing_png(vec![
+        chunk("sRGB", &[0]),
+        chunk("gAMA", &[0, 0, 177, 143]),
+        chunk("tEXt", b"Author\0Someone"),
+        chunk("tEXt", b"Title\0Dice!"),
+    ]);
+    let diff = diff(&old_png(), &new, &[]);
+    assert_eq!(
+        lines(&diff),
+        [
+            "~ chunk 4 tEXt (Author) moved to chunk 3",
+            "* chunk 4 tEXt (Title) changed",
+            r#"    text: "Dice" -> "Dice!""#
+        ]
+    );
+    assert!(diff.chunks[0].moved);
+    assert!(!diff.chunks[1].moved);
+}
+
+#[test]
+fn test_ignore() {
+    let new = testing_png(vec![
+        chunk("sRGB", &[0]),
+        chunk("gAMA", &[0, 0, 177, 143]),
+        chunk("tIME", &[7, 234, 10, 16, 9, 5, 0]),
+        chunk("tEXt", b"Title\0Dice"),
+        chunk("tEXt", b"Author\0Someone"),
+    ]);
+    let ignore = [ChunkType::tIME];
+    assert!(diff(&old_png(), &new, &ignore).is_empty());
+}
+
+#[test]
+fn test_trailing_bytes() {
+    let mut new = old_png();
+    new.set_trailing_bytes(vec![1, 2]);
+    let diff = diff(&old_png(), &new, &[]);
+    assert!(diff.chunks.is_empty());
+    assert_eq!(
+        lines(&diff),
+        ["trailing bytes changed: bytes 0..0 of 0 became 0..2 of 2"]
+    );
+}
+
+#[test]
+fn test_json() {
+    let new = testing_png(vec![
+        chunk("sRGB", &[0]),
+        chunk("tEXt", b"Title\0Dice"),
+        chunk("tEXt", b"Author\0Someone"),
+    ]);
+    let json = serde_json::to_value(diff(&old_png(), &new, &[])).unwrap();
+    assert_eq!(
+        json,
+        serde_json::json!({
+            "chunks": [{
+                "kind": "removed",
+                "type": "gAMA",
+                "old_index": 2,
+                "new_index": null,
+                "moved": false
+            }]
+        })
+    );
+}
+
+#[test]
+fn test_longest_increasing() {
+    assert_eq!(
+        longest_increasing(&[0, 3, 1, 2, 4]),
+        [true, false, true, true, true]
+    );
+    assert_eq!(longest_increasing(&[]), Vec::<bool>::new());
+}
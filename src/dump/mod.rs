@@ -0,0 +1,159 @@
+//! A plain description of a png's chunk list that can be saved as JSON,
+//! edited by tools that know nothing about PNG, and built back into a png.
+//! Chunk data is kept as text when it is printable UTF-8 and as base64
+//! otherwise, so that every byte survives the round trip.
+
+#[cfg(test)]
+mod unit_tests;
+
+use std::str::{self, FromStr};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::{Deserialize, Serialize};
+
+use crate::{chunk::Chunk, chunk_type::ChunkType, error::PngError, png::Png};
+
+/// Every chunk of a png, in file order, and whatever bytes follow them.
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug)]
+pub struct Dump {
+    pub chunks: Vec<ChunkDump>,
+    /// Bytes after IEND, or that couldn't be parsed as chunks
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trailing: Option<Data>,
+}
+
+/// One chunk of a dump. Only the type and data are read back by [`build`];
+/// the other fields describe the chunk as it was dumped and are worked out
+/// again when it is built, so they can be left out of new chunks.
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug)]
+pub struct ChunkDump {
+    #[serde(default)]
+    pub index: usize,
+    /// Byte position of the start of the chunk in the file
+    #[serde(default)]
+    pub offset: u64,
+    /// Length of the chunk data
+    #[serde(default)]
+    pub length: u32,
+    #[serde(rename = "type")]
+    pub chunk_type: String,
+    #[serde(default)]
+    pub properties: Properties,
+    /// CRC as stored in the file, which may not match the data
+    #[serde(default)]
+    pub crc: u32,
+    pub data: Data,
+}
+
+/// The four property bits of a [`ChunkType`].
+#[derive(Serialize, Deserialize, Default, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Properties {
+    pub critical: bool,
+    pub public: bool,
+    pub reserved_bit_valid: bool,
+    pub safe_to_copy: bool,
+}
+
+impl From<&ChunkType> for Properties {
+    fn from(chunk_type: &ChunkType) -> Self {
+        Properties {
+            critical: chunk_type.is_critical(),
+            public: chunk_type.is_public(),
+            reserved_bit_valid: chunk_type.is_reserved_bit_valid(),
+            safe_to_copy: chunk_type.is_safe_to_copy(),
+        }
+    }
+}
+
+/// Bytes written as `{"text": "..."}` or `{"base64": "..."}`.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum Data {
+    Text(String),
+    Base64(String),
+}
+
+impl Data {
+    /// Text if the bytes are UTF-8 without control characters other than
+    /// NUL, tab and line breaks, otherwise base64
+    pub fn encode(bytes: &[u8]) -> Data {
+        let printable = |c: char| !c.is_control() || matches!(c, '\0' | '\t' | '\n' | '\r');
+        match str::from_utf8(bytes) {
+            Ok(text) if text.chars().all(printable) => Data::Text(text.to_string()),
+            _ => Data::Base64(STANDARD.encode(bytes)),
+        }
+    }
+    /// The bytes this stands for
+    pub fn decode(&self) -> Result<Vec<u8>, PngError> {
+        match self {
+            Data::Text(text) => Ok(text.as_bytes().to_vec()),
+            Data::Base64(encoded) => {
+                STANDARD
+                    .decode(encoded)
+                    .map_err(|err| PngError::InvalidDump {
+                        reason: format!("invalid base64: {err}"),
+                        offset: 0,
+                        index: 0,
+                    })
+            }
+        }
+    }
+}
+
+/// Describe every chunk of a png
+pub fn dump(png: &Png) -> Dump {
+    let mut offset = Png::STANDARD_HEADER.len() as u64;
+    let chunks = png
+        .chunks()
+        .iter()
+        .enumerate()
+        .map(|(index, chunk)| {
+            let record = ChunkDump {
+                index,
+                offset,
+                length: chunk.length(),
+                chunk_type: chunk.chunk_type().to_string(),
+                properties: Properties::from(chunk.chunk_type()),
+                crc: chunk.written_crc(),
+                data: Data::encode(chunk.data()),
+            };
+            offset += u64::from(chunk.length()) + 12;
+            record
+        })
+        .collect();
+    let trailing = Some(png.trailing_bytes())
+        .filter(|trailing| !trailing.is_empty())
+        .map(Data::encode);
+    Dump { chunks, trailing }
+}
+
+/// Build a png from the types and data of a dump's chunks. CRCs are
+/// calculated afresh, so edited data doesn't need a new one. Errors are
+/// reported at the position the chunk would have in the new file.
+pub fn build(dump: &Dump) -> Result<Png, PngError> {
+    let mut chunks = Vec::with_capacity(dump.chunks.len());
+    let mut offset = Png::STANDARD_HEADER.len() as u64;
+    for (index, record) in dump.chunks.iter().enumerate() {
+        let locate = |err: PngError| err.at(offset, index);
+        let chunk_type = ChunkType::from_str(&record.chunk_type).map_err(locate)?;
+        let data = record.data.decode().map_err(locate)?;
+        if data.len() as u64 > u64::from(Chunk::MAX_LENGTH) {
+            return Err(PngError::LengthOverflow {
+                length: data.len() as u64,
+                offset,
+                index,
+            });
+        }
+        let chunk = Chunk::new(chunk_type, data);
+        offset += u64::from(chunk.length()) + 12;
+        chunks.push(chunk);
+    }
+    let mut png = Png::from_chunks(chunks);
+    if let Some(trailing) = &dump.trailing {
+        let trailing = trailing
+            .decode()
+            .map_err(|err| err.at(offset, dump.chunks.len()))?;
+        png.set_trailing_bytes(trailing);
+    }
+    Ok(png)
+}
@@ -0,0 +1,96 @@
+use super::*;
+use crate::test_utils::chunk;
+
+fn testing_png() -> Png {
+    let mut png = Png::from_chunks(vec![
+        chunk("IHDR", &[0, 0, 0, 1, 0, 0, 0, 1, 8, 0, 0, 0, 0]),
+        chunk("tEXt", b"Title\0Dice"),
+        chunk("ruSt", b"line one\nline two"),
+        chunk("IDAT", &[120, 156, 99, 0, 0, 0, 2, 0, 1]),
+        chunk("IEND", &[]),
+    ]);
+    png.set_trailing_bytes(vec![0xff, 0xfe]);
+    png
+}
+
+#[test]
+fn test_dump() {
+    let png = testing_png();
+    let dump = dump(&png);
+    assert_eq!(dump.chunks.len(), 5);
+    let text = &dump.chunks[1];
+    assert_eq!((text.index, text.offset, text.length), (1, 33, 10));
+    assert_eq!(text.chunk_type, "tEXt");
+    assert_eq!(text.crc, png.chunks()[1].crc());
+    assert_eq!(text.data, Data::Text("Title\0Dice".to_string()));
+    assert_eq!(
+        text.properties,
+        Properties {
+            critical: false,
+            public: true,
+            reserved_bit_valid: true,
+            safe_to_copy: true,
+        }
+    );
+    assert!(dump.chunks[0].properties.critical);
+    assert!(!dump.chunks[2].properties.public);
+    assert_eq!(
+        dump.chunks[3].data,
+        Data::Base64("eJxjAAAAAgAB".to_string())
+    );
+    assert_eq!(dump.trailing, Some(Data::Base64("//4=".to_string())));
+}
+
+#[test]
+fn test_json_round_trip() {
+    let png = testing_png();
+    let json = serde_json::to_string_pretty(&dump(&png)).unwrap();
+    assert!(json.contains(r#""type": "ruSt""#));
+    assert!(json.contains(r#""text": "line one\nline two""#));
+    let parsed: Dump = serde_json::from_str(&json).unwrap();
+    assert_eq!(build(&parsed).unwrap().as_bytes(), png.as_bytes());
+}
+
+#[test]
+fn test_build_needs_only_type_and_data() {
+    let json = r#"{"chunks": [
+        {"type": "IHDR", "data": {"base64": "AAAAAQAAAAEIAAAAAA=="}},
+        {"type": "tEXt", "data": {"text": "Comment\u0000added by a script"}},
+        {"type": "IDAT", "data": {"base64": "eJxjAAAAAgAB"}},
+        {"type": "IEND", "data": {"text": ""}}
+    ]}"#;
+    let png = build(&serde_json::from_str(json).unwrap()).unwrap();
+    assert!(png.validate().is_empty());
+    assert_eq!(
+        png.text("Comment").unwrap().unwrap().text,
+        "added by a script"
+    );
+    assert!(png.trailing_bytes().is_empty());
+}
+
+#[test]
+fn test_build_recalculates_crc() {
+    let mut dump = dump(&testing_png());
+    dump.chunks[2].data = Data::Text("edited".to_string());
+    let png = build(&dump).unwrap();
+    assert!(png.chunks()[2].verify_crc().is_ok());
+    assert_eq!(png.chunks()[2].data(), b"edited");
+}
+
+#[test]
+fn test_build_invalid_chunk_type() {
+    let mut dump = dump(&testing_png());
+    dump.chunks[2].chunk_type = "ru5t".to_string();
+    let err = build(&dump).err().unwrap();
+    assert!(matches!(err, PngError::InvalidChunkType { .. }));
+    assert_eq!((err.offset(), err.index()), (55, 2));
+}
+
+#[test]
+fn test_build_invalid_base64() {
+    let mut dump = dump(&testing_png());
+    dump.chunks[3].data = Data::Base64("not base64!".to_string());
+    let err = build(&dump).err().unwrap();
+    assert_eq!(err.index(), 3);
+    assert!(err.to_string().contains("invalid base64"));
+}
@@ -0,0 +1,205 @@
+//! Building a png from scratch out of raw pixels. The pixels are filtered
+//! a scanline at a time, interlaced if asked, zlib compressed and split
+//! across IDAT chunks, then wrapped in IHDR, PLTE and IEND.
+
+#[cfg(test)]
+mod unit_tests;
+
+use crate::{
+    adam7,
+    chunk::Chunk,
+    chunk_type::ChunkType,
+    error::PngError,
+    filter::{self, FilterStrategy},
+    image,
+    image_header::{ColorType, ImageHeader, InterlaceMethod},
+    png::Png,
+    zlib,
+};
+
+/// Default size of each IDAT chunk written
+pub const DEFAULT_IDAT_LENGTH: usize = 1 << 16;
+
+/// Choices that change how an image is stored but not what it looks like.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct EncodeOptions {
+    /// How to choose the filter for each scanline
+    pub filter: FilterStrategy,
+    pub interlace_method: InterlaceMethod,
+    /// zlib level from 0 (store) to 9 (smallest)
+    pub compression_level: u32,
+    /// Largest IDAT chunk to write; the compressed data is split across as
+    /// many as it needs
+    pub idat_length: usize,
+    /// RGB palette entries, required for indexed images and optional for
+    /// RGB ones
+    pub palette: Vec<[u8; 3]>,
+}
+
+impl Default for EncodeOptions {
+    /// Adaptive filtering and the best compression, not interlaced
+    fn default() -> Self {
+        EncodeOptions {
+            filter: FilterStrategy::Adaptive,
+            interlace_method: InterlaceMethod::None,
+            compression_level: 9,
+            idat_length: DEFAULT_IDAT_LENGTH,
+            palette: Vec::new(),
+        }
+    }
+}
+
+/// Build a png from scanlines of raw pixels, with the default options.
+/// See [`encode_with_options`].
+pub fn encode(
+    width: u32,
+    height: u32,
+    color_type: ColorType,
+    bit_depth: u8,
+    pixels: &[u8],
+) -> Result<Png, PngError> {
+    encode_with_options(
+        width,
+        height,
+        color_type,
+        bit_depth,
+        pixels,
+        &EncodeOptions::default(),
+    )
+}
+
+/// Build a png from scanlines of raw pixels. `pixels` holds the rows from
+/// top to bottom, with samples stored the way the spec lays them out:
+/// 16-bit samples big endian, samples smaller than a byte packed from the
+/// most significant bit down and each row padded to a whole byte.
+pub fn encode_with_options(
+    width: u32,
+    height: u32,
+    color_type: ColorType,
+    bit_depth: u8,
+    pixels: &[u8],
+    options: &EncodeOptions,
+) -> Result<Png, PngError> {
+    let mut bytes = [0; ImageHeader::LENGTH];
+    bytes[0..4].copy_from_slice(&width.to_be_bytes());
+    bytes[4..8].copy_from_slice(&height.to_be_bytes());
+    bytes[8] = bit_depth;
+    bytes[9] = color_type as u8;
+    bytes[12] = options.interlace_method as u8;
+    let header = ImageHeader::try_from(&bytes[..])?;
+    if options.compression_level > 9 {
+        return Err(invalid(format!(
+            "zlib level {} is out of range, levels go from 0 to 9",
+            options.compression_level
+        )));
+    }
+
+    let expected = header.row_bytes(width) as u64 * u64::from(height);
+    if pixels.len() as u64 != expected {
+        return Err(invalid(format!(
+            "pixel buffer is {} bytes, expected {expected} for a {header} image",
+            pixels.len()
+        )));
+    }
+    check_palette(&header, pixels, &options.palette)?;
+
+    let mut chunks = vec![header.to_chunk()];
+    if !options.palette.is_empty() {
+        chunks.push(Chunk::new(ChunkType::PLTE, options.palette.concat()));
+    }
+    chunks.extend(image_data_chunks(pixels, &header, options));
+    chunks.push(Chunk::new(ChunkType::IEND, Vec::new()));
+    Ok(Png::from_chunks(chunks))
+}
+
+/// Filter, compress and split scanlines into IDAT chunks, interlacing them
+/// if the header says so. The interlace method in the options is ignored.
+pub fn image_data_chunks(raw: &[u8], header: &ImageHeader, options: &EncodeOptions) -> Vec<Chunk> {
+    let data = zlib::deflate(
+        &encode_scanlines(raw, header, options.filter),
+        options.compression_level,
+    );
+    data.chunks(options.idat_length.max(1))
+        .map(|data| Chunk::new(ChunkType::IDAT, data.to_vec()))
+        .collect()
+}
+
+/// Filter scanlines into the image data that gets compressed, split into
+/// passes first if the header says the image is interlaced
+pub fn encode_scanlines(raw: &[u8], header: &ImageHeader, strategy: FilterStrategy) -> Vec<u8> {
+    let bytes_per_pixel = header.bits_per_pixel().div_ceil(8);
+    match header.interlace_method {
+        InterlaceMethod::None => filter::filter(
+            raw,
+            header.row_bytes(header.width),
+            header.height as usize,
+            bytes_per_pixel,
+            strategy,
+        ),
+        InterlaceMethod::Adam7 => adam7::interlace(raw, header)
+            .iter()
+            .zip(adam7::pass_sizes(header).expect("passes of scanlines in memory fit in a u64"))
+            // passes without any pixels don't even have filter type bytes
+            .filter(|(_, size)| size.data_length > 0)
+            .flat_map(|(pass, size)| {
+                filter::filter(
+                    pass,
+                    header.row_bytes(size.width),
+                    size.height as usize,
+                    bytes_per_pixel,
+                    strategy,
+                )
+            })
+            .collect(),
+    }
+}
+
+/// Make sure indexed images have a palette big enough for every pixel,
+/// and that grayscale images don't have one at all
+fn check_palette(header: &ImageHeader, pixels: &[u8], palette: &[[u8; 3]]) -> Result<(), PngError> {
+    match header.color_type {
+        ColorType::Indexed => {
+            let max = 1 << header.bit_depth;
+            if palette.is_empty() || palette.len() > max {
+                return Err(invalid(format!(
+                    "palette has {} entries, expected 1 to {max} for a {header} image",
+                    palette.len()
+                )));
+            }
+            let row_bytes = header.row_bytes(header.width);
+            for row in pixels.chunks_exact(row_bytes) {
+                let outside = image::samples(row, header.bit_depth)
+                    .take(header.width as usize)
+                    .find(|&index| usize::from(index) >= palette.len());
+                if let Some(index) = outside {
+                    return Err(invalid(format!(
+                        "palette index {index} is outside the {} entry palette",
+                        palette.len()
+                    )));
+                }
+            }
+        }
+        ColorType::Grayscale | ColorType::GrayscaleAlpha if !palette.is_empty() => {
+            return Err(invalid(format!(
+                "{} images can't have a palette",
+                header.color_type
+            )));
+        }
+        _ if palette.len() > 256 => {
+            return Err(invalid(format!(
+                "palette has {} entries, more than the maximum of 256",
+                palette.len()
+            )));
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+fn invalid(reason: String) -> PngError {
+    PngError::InvalidImage {
+        reason,
+        offset: 0,
+        index: 0,
+    }
+}
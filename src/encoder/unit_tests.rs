@@ -0,0 +1,135 @@
+use super::*;
+use crate::filter::FilterType;
+
+/// Made up scanlines for an image, with any padding bits at the end of
+/// each row left as zero
+fn testing_pixels(width: u32, height: u32, color_type: ColorType, bit_depth: u8) -> Vec<u8> {
+    let bits = width as usize * color_type.channels() * usize::from(bit_depth);
+    let row_bytes = bits.div_ceil(8);
+    let padding = row_bytes * 8 - bits;
+    let mut pixels = Vec::new();
+    for y in 0..height as usize {
+        let mut row: Vec<u8> = (0..row_bytes).map(|x| (x * 31 + y * 7) as u8).collect();
+        if let Some(last) = row.last_mut() {
+            *last &= 0xff << padding;
+        }
+        pixels.extend(row);
+    }
+    pixels
+}
+
+#[test]
+fn test_encode_rgba() {
+    let pixels = [255, 0, 0, 255, 0, 255, 0, 128, 0, 0, 255, 0, 9, 9, 9, 9];
+    let png = encode(2, 2, ColorType::Rgba, 8, &pixels).unwrap();
+    assert_eq!(png.to_string(), "IHDR\r\nIDAT\r\nIEND\r\n");
+    assert!(png.validate().is_empty());
+    let reread = Png::try_from(png.as_bytes().as_slice()).unwrap();
+    assert_eq!(reread.image().unwrap().pixels(), pixels);
+}
+
+#[test]
+fn test_every_format_round_trips() {
+    for color_type in [
+        ColorType::Grayscale,
+        ColorType::Rgb,
+        ColorType::GrayscaleAlpha,
+        ColorType::Rgba,
+    ] {
+        for &bit_depth in color_type.allowed_bit_depths() {
+            for interlace_method in [InterlaceMethod::None, InterlaceMethod::Adam7] {
+                let pixels = testing_pixels(11, 7, color_type, bit_depth);
+                let options = EncodeOptions {
+                    interlace_method,
+                    ..Default::default()
+                };
+                let png =
+                    encode_with_options(11, 7, color_type, bit_depth, &pixels, &options).unwrap();
+                let (header, raw) = image::scanlines(&png).unwrap();
+                assert_eq!(header.interlace_method, interlace_method);
+                assert_eq!(raw, pixels, "{header}");
+            }
+        }
+    }
+}
+
+#[test]
+fn test_filter_choices() {
+    let pixels = testing_pixels(20, 10, ColorType::Rgb, 8);
+    let strategies = FilterType::ALL
+        .map(FilterStrategy::Fixed)
+        .into_iter()
+        .chain([FilterStrategy::Adaptive]);
+    for filter in strategies {
+        let options = EncodeOptions {
+            filter,
+            ..Default::default()
+        };
+        let png = encode_with_options(20, 10, ColorType::Rgb, 8, &pixels, &options).unwrap();
+        assert_eq!(image::scanlines(&png).unwrap().1, pixels, "{filter}");
+    }
+}
+
+#[test]
+fn test_split_image_data() {
+    let pixels = testing_pixels(64, 64, ColorType::Rgba, 16);
+    let options = EncodeOptions {
+        idat_length: 100,
+        compression_level: 0,
+        ..Default::default()
+    };
+    let png = encode_with_options(64, 64, ColorType::Rgba, 16, &pixels, &options).unwrap();
+    let idats: Vec<&Chunk> = png
+        .chunks()
+        .iter()
+        .filter(|chunk| chunk.chunk_type().bytes() == *b"IDAT")
+        .collect();
+    assert!(idats.len() > 1);
+    assert!(idats.iter().all(|chunk| chunk.length() <= 100));
+    assert_eq!(image::scanlines(&png).unwrap().1, pixels);
+}
+
+#[test]
+fn test_indexed() {
+    let options = EncodeOptions {
+        palette: vec![[255, 0, 0], [0, 0, 255]],
+        ..Default::default()
+    };
+    let png = encode_with_options(3, 1, ColorType::Indexed, 1, &[0b0100_0000], &options).unwrap();
+    assert_eq!(png.to_string(), "IHDR\r\nPLTE\r\nIDAT\r\nIEND\r\n");
+    assert_eq!(
+        png.image().unwrap().pixels(),
+        [255, 0, 0, 255, 0, 0, 255, 255, 255, 0, 0, 255]
+    );
+}
+
+#[test]
+fn test_bad_palette() {
+    let no_palette = encode(2, 1, ColorType::Indexed, 8, &[0, 1]);
+    assert!(matches!(no_palette, Err(PngError::InvalidImage { .. })));
+
+    let options = EncodeOptions {
+        palette: vec![[0, 0, 0]],
+        ..Default::default()
+    };
+    let outside = encode_with_options(2, 1, ColorType::Indexed, 8, &[0, 1], &options);
+    assert!(matches!(outside, Err(PngError::InvalidImage { .. })));
+    let grayscale = encode_with_options(2, 1, ColorType::Grayscale, 8, &[0, 1], &options);
+    assert!(matches!(grayscale, Err(PngError::InvalidImage { .. })));
+}
+
+#[test]
+fn test_bad_dimensions() {
+    assert!(matches!(
+        encode(2, 2, ColorType::Rgb, 8, &[0; 11]),
+        Err(PngError::InvalidImage { .. })
+    ));
+    assert!(matches!(
+        encode(0, 2, ColorType::Rgb, 8, &[]),
+        Err(PngError::InvalidHeader { .. })
+    ));
+    assert!(matches!(
+        encode(2, 2, ColorType::Rgb, 4, &[0; 6]),
+        Err(PngError::InvalidHeader { .. })
+    ));
+}
@@ -0,0 +1,253 @@
+//! Errors produced while parsing, searching and writing PNG files.
+//!
+//! Every variant records where the problem was found: `offset` is the byte
+//! position of the start of the chunk (or signature) being processed and
+//! `index` is the zero based number of that chunk in the file. Errors raised
+//! while looking at a single chunk on its own are reported at offset 0 and
+//! index 0, and are moved to their real position by whatever is parsing the
+//! whole file.
+
+use std::str::Utf8Error;
+
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum PngError {
+    /// The first 8 bytes are not [`Png::STANDARD_HEADER`](crate::png::Png)
+    #[error("signature {found:?} does not match expected at byte {offset}")]
+    BadSignature {
+        found: Vec<u8>,
+        offset: u64,
+        index: usize,
+    },
+    /// A chunk type that is not 4 ASCII letters
+    #[error("chunk type {bytes:?} is invalid in chunk {index} at byte {offset}")]
+    InvalidChunkType {
+        bytes: Vec<u8>,
+        offset: u64,
+        index: usize,
+    },
+    /// The input ended before the chunk did
+    #[error(
+        "chunk {index} at byte {offset} is truncated, needed {needed} bytes but only {available} are left"
+    )]
+    TruncatedChunk {
+        needed: u64,
+        available: u64,
+        offset: u64,
+        index: usize,
+    },
+    /// The CRC stored in the file (`actual`) doesn't match the one calculated
+    /// from the chunk type and data (`expected`)
+    #[error("crc {actual} didn't match calculated {expected} in {chunk_type} chunk {index} at byte {offset}")]
+    CrcMismatch {
+        chunk_type: String,
+        expected: u32,
+        actual: u32,
+        offset: u64,
+        index: usize,
+    },
+    /// No chunk of the requested type. `index` is the number of chunks that
+    /// were searched and `offset` the size of the file they make up.
+    #[error("chunk {chunk_type} not found in {index} chunks")]
+    ChunkNotFound {
+        chunk_type: String,
+        offset: u64,
+        index: usize,
+    },
+    /// A chunk length above the 2^31 - 1 limit set by the spec
+    #[error(
+        "chunk {index} at byte {offset} has length {length}, more than the maximum of 2^31 - 1"
+    )]
+    LengthOverflow {
+        length: u64,
+        offset: u64,
+        index: usize,
+    },
+    /// An IHDR chunk that doesn't follow the rules of the spec
+    #[error("invalid IHDR chunk {index} at byte {offset}: {reason}")]
+    InvalidHeader {
+        reason: String,
+        offset: u64,
+        index: usize,
+    },
+    /// Compressed data that is not a valid zlib stream, or that inflates
+    /// past the allowed size
+    #[error("invalid compressed data in chunk {index} at byte {offset}: {reason}")]
+    Decompression {
+        reason: String,
+        offset: u64,
+        index: usize,
+    },
+    /// Image data that can't be decoded into pixels
+    #[error("invalid image data in chunk {index} at byte {offset}: {reason}")]
+    InvalidImage {
+        reason: String,
+        offset: u64,
+        index: usize,
+    },
+    /// An ancillary chunk whose data doesn't have the layout its type
+    /// requires
+    #[error("invalid {chunk_type} chunk {index} at byte {offset}: {reason}")]
+    InvalidChunk {
+        chunk_type: String,
+        reason: String,
+        offset: u64,
+        index: usize,
+    },
+    /// APNG chunks that are malformed or don't fit together
+    #[error("invalid animation in chunk {index} at byte {offset}: {reason}")]
+    InvalidAnimation {
+        reason: String,
+        offset: u64,
+        index: usize,
+    },
+    /// A chunk in a JSON dump that can't be turned back into a chunk
+    #[error("invalid dump of chunk {index} at byte {offset}: {reason}")]
+    InvalidDump {
+        reason: String,
+        offset: u64,
+        index: usize,
+    },
+    /// A tEXt, zTXt or iTXt chunk that can't be decoded or encoded
+    #[error("invalid text chunk {index} at byte {offset}: {reason}")]
+    InvalidText {
+        reason: String,
+        offset: u64,
+        index: usize,
+    },
+    /// An encrypted message that is malformed or can't be sealed
+    #[error("invalid encrypted message in chunk {index} at byte {offset}: {reason}")]
+    InvalidCiphertext {
+        reason: String,
+        offset: u64,
+        index: usize,
+    },
+    /// An encrypted message that doesn't match the passphrase, either
+    /// because the passphrase is wrong or because the message was changed
+    #[error("wrong passphrase or tampered message in chunk {index} at byte {offset}")]
+    AuthenticationFailed { offset: u64, index: usize },
+    /// A split payload with pieces that are missing or appear more than once
+    #[error(
+        "payload is incomplete, missing pieces {missing:?} and duplicated pieces {duplicated:?}"
+    )]
+    IncompletePayload {
+        missing: Vec<u32>,
+        duplicated: Vec<u32>,
+        offset: u64,
+        index: usize,
+    },
+    /// A piece of a split payload that is malformed, or a payload that can't
+    /// be split or joined
+    #[error("invalid payload piece {index} at byte {offset}: {reason}")]
+    InvalidPayload {
+        reason: String,
+        offset: u64,
+        index: usize,
+    },
+    /// A message too long to hide in the pixels of an image
+    #[error("message of {length} bytes doesn't fit, the pixels can hold at most {capacity}")]
+    MessageTooLarge {
+        length: u64,
+        capacity: u64,
+        offset: u64,
+        index: usize,
+    },
+    /// Pixels that don't hold a hidden message with the expected tag, or
+    /// whose message is malformed
+    #[error("no message found in the pixels of chunk {index} at byte {offset}: {reason}")]
+    NoHiddenMessage {
+        reason: String,
+        offset: u64,
+        index: usize,
+    },
+    /// A chunk written after the IEND chunk that ends the file
+    #[error(
+        "can't write {chunk_type} chunk {index} at byte {offset}, IEND has already been written"
+    )]
+    ChunkAfterEnd {
+        chunk_type: String,
+        offset: u64,
+        index: usize,
+    },
+    /// Bytes that don't belong to any chunk, skipped while recovering a
+    /// damaged file
+    #[error("skipped {length} unreadable bytes at byte {offset}, before chunk {index}")]
+    Garbage {
+        length: u64,
+        offset: u64,
+        index: usize,
+    },
+    /// Chunk data that was expected to be UTF-8 text but isn't
+    #[error("chunk {index} data is not valid utf-8")]
+    InvalidUtf8 {
+        source: Utf8Error,
+        offset: u64,
+        index: usize,
+    },
+    /// Reading or writing the underlying stream failed
+    #[error("i/o error in chunk {index} at byte {offset}")]
+    Io {
+        source: std::io::Error,
+        offset: u64,
+        index: usize,
+    },
+}
+
+/// Implement `offset`, `index` and `at` for the listed variants, which must
+/// be every variant and all have `offset` and `index` fields
+macro_rules! impl_position {
+    ($($variant:ident),+ $(,)?) => {
+        impl PngError {
+            /// Byte offset of the start of the chunk the error happened in
+            pub fn offset(&self) -> u64 {
+                match self {
+                    $(PngError::$variant { offset, .. })|+ => *offset,
+                }
+            }
+            /// Index of the chunk the error happened in
+            pub fn index(&self) -> usize {
+                match self {
+                    $(PngError::$variant { index, .. })|+ => *index,
+                }
+            }
+            /// Move an error found in a single chunk to that chunk's position
+            /// in the file
+            pub fn at(mut self, at_offset: u64, at_index: usize) -> Self {
+                match &mut self {
+                    $(PngError::$variant { offset, index, .. })|+ => {
+                        *offset = at_offset;
+                        *index = at_index;
+                    }
+                }
+                self
+            }
+        }
+    };
+}
+
+impl_position!(
+    BadSignature,
+    InvalidChunkType,
+    TruncatedChunk,
+    CrcMismatch,
+    ChunkNotFound,
+    LengthOverflow,
+    InvalidHeader,
+    Decompression,
+    InvalidImage,
+    InvalidChunk,
+    InvalidAnimation,
+    InvalidDump,
+    InvalidText,
+    InvalidCiphertext,
+    AuthenticationFailed,
+    IncompletePayload,
+    InvalidPayload,
+    MessageTooLarge,
+    NoHiddenMessage,
+    ChunkAfterEnd,
+    Garbage,
+    InvalidUtf8,
+    Io,
+);
@@ -0,0 +1,260 @@
+//! Scanline filters. Before compression each scanline is prefixed with a
+//! filter type byte and its bytes are replaced by the difference from a
+//! prediction made from the pixel to the left, the pixel above, or both.
+//! Filters work on bytes, not pixels: "the pixel to the left" is the byte
+//! one whole pixel back, or one byte back when pixels are smaller than a
+//! byte. See [Filter Algorithms](http://www.libpng.org/pub/png/spec/1.2/PNG-Filters.html).
+
+#[cfg(test)]
+mod unit_tests;
+
+use std::fmt::Display;
+
+use crate::error::PngError;
+
+/// The five filter types of filter method 0.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum FilterType {
+    /// The scanline is stored unchanged.
+    None = 0,
+    /// Each byte is stored as the difference from the byte to its left.
+    Sub = 1,
+    /// Each byte is stored as the difference from the byte above it.
+    Up = 2,
+    /// Each byte is stored as the difference from the mean of the bytes to
+    /// its left and above it.
+    Average = 3,
+    /// Each byte is stored as the difference from whichever of left, above
+    /// or upper left is closest to left + above - upper left.
+    Paeth = 4,
+}
+
+impl FilterType {
+    /// Every filter type, in order of their type byte
+    pub const ALL: [FilterType; 5] = [
+        FilterType::None,
+        FilterType::Sub,
+        FilterType::Up,
+        FilterType::Average,
+        FilterType::Paeth,
+    ];
+}
+
+impl TryFrom<u8> for FilterType {
+    type Error = u8;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        FilterType::ALL
+            .get(usize::from(value))
+            .copied()
+            .ok_or(value)
+    }
+}
+
+impl Display for FilterType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            FilterType::None => "none",
+            FilterType::Sub => "sub",
+            FilterType::Up => "up",
+            FilterType::Average => "average",
+            FilterType::Paeth => "paeth",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// How to choose the filter for each scanline when encoding.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum FilterStrategy {
+    /// Use the same filter for every scanline
+    Fixed(FilterType),
+    /// Try every filter on each scanline and keep the one whose output has
+    /// the smallest sum of absolute values, reading bytes as signed. This
+    /// is the heuristic the spec suggests and tends to compress best.
+    #[default]
+    Adaptive,
+}
+
+impl Display for FilterStrategy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FilterStrategy::Fixed(filter) => write!(f, "{filter}"),
+            FilterStrategy::Adaptive => write!(f, "adaptive"),
+        }
+    }
+}
+
+/// The Paeth predictor: whichever of `left`, `above` and `upper_left` is
+/// closest to `left + above - upper_left`, preferring them in that order
+fn paeth(left: u8, above: u8, upper_left: u8) -> u8 {
+    let (a, b, c) = (i16::from(left), i16::from(above), i16::from(upper_left));
+    let p = a + b - c;
+    let (pa, pb, pc) = ((p - a).abs(), (p - b).abs(), (p - c).abs());
+    if pa <= pb && pa <= pc {
+        left
+    } else if pb <= pc {
+        above
+    } else {
+        upper_left
+    }
+}
+
+/// Filter one scanline, appending the filter type byte and the filtered
+/// bytes to `out`. `previous` is the unfiltered scanline above, all zeros
+/// for the first row.
+pub fn filter_row(
+    filter: FilterType,
+    row: &[u8],
+    previous: &[u8],
+    bytes_per_pixel: usize,
+    out: &mut Vec<u8>,
+) {
+    out.push(filter as u8);
+    for i in 0..row.len() {
+        let left = i.checked_sub(bytes_per_pixel).map_or(0, |j| row[j]);
+        let upper_left = i.checked_sub(bytes_per_pixel).map_or(0, |j| previous[j]);
+        let predictor = match filter {
+            FilterType::None => 0,
+            FilterType::Sub => left,
+            FilterType::Up => previous[i],
+            FilterType::Average => ((u16::from(left) + u16::from(previous[i])) / 2) as u8,
+            FilterType::Paeth => paeth(left, previous[i], upper_left),
+        };
+        out.push(row[i].wrapping_sub(predictor));
+    }
+}
+
+/// Filter `rows` scanlines of `row_bytes` bytes each, choosing the filter
+/// for each one with `strategy`. Returns the scanlines each prefixed with
+/// their filter type byte.
+pub fn filter(
+    raw: &[u8],
+    row_bytes: usize,
+    rows: usize,
+    bytes_per_pixel: usize,
+    strategy: FilterStrategy,
+) -> Vec<u8> {
+    let mut filtered = Vec::with_capacity((row_bytes + 1) * rows);
+    let zeros = vec![0; row_bytes];
+    let mut candidate = Vec::with_capacity(row_bytes + 1);
+    for row in 0..rows {
+        let current = &raw[row * row_bytes..][..row_bytes];
+        let previous = match row {
+            0 => &zeros[..],
+            _ => &raw[(row - 1) * row_bytes..][..row_bytes],
+        };
+        match strategy {
+            FilterStrategy::Fixed(filter) => {
+                filter_row(filter, current, previous, bytes_per_pixel, &mut filtered)
+            }
+            FilterStrategy::Adaptive => {
+                let cost = |filtered: &[u8]| -> u64 {
+                    filtered[1..]
+                        .iter()
+                        .map(|&byte| u64::from((byte as i8).unsigned_abs()))
+                        .sum()
+                };
+                let mut best: Option<(u64, FilterType)> = None;
+                for filter in FilterType::ALL {
+                    candidate.clear();
+                    filter_row(filter, current, previous, bytes_per_pixel, &mut candidate);
+                    let cost = cost(&candidate);
+                    if best.is_none_or(|(best_cost, _)| cost < best_cost) {
+                        best = Some((cost, filter));
+                    }
+                }
+                let (_, filter) = best.expect("there is always a filter to try");
+                filter_row(filter, current, previous, bytes_per_pixel, &mut filtered);
+            }
+        }
+    }
+    filtered
+}
+
+/// Undo the filter on one scanline in place. `previous` is the unfiltered
+/// scanline above, all zeros for the first row, and `bytes_per_pixel` is
+/// the distance back to the corresponding byte of the pixel to the left.
+pub fn unfilter_row(filter: FilterType, row: &mut [u8], previous: &[u8], bytes_per_pixel: usize) {
+    match filter {
+        FilterType::None => {}
+        FilterType::Sub => {
+            for i in bytes_per_pixel..row.len() {
+                row[i] = row[i].wrapping_add(row[i - bytes_per_pixel]);
+            }
+        }
+        FilterType::Up => {
+            for (byte, above) in row.iter_mut().zip(previous) {
+                *byte = byte.wrapping_add(*above);
+            }
+        }
+        FilterType::Average => {
+            for i in 0..row.len() {
+                let left = match i.checked_sub(bytes_per_pixel) {
+                    Some(j) => u16::from(row[j]),
+                    None => 0,
+                };
+                let mean = (left + u16::from(previous[i])) / 2;
+                row[i] = row[i].wrapping_add(mean as u8);
+            }
+        }
+        FilterType::Paeth => {
+            for i in 0..row.len() {
+                let predictor = match i.checked_sub(bytes_per_pixel) {
+                    Some(j) => paeth(row[j], previous[i], previous[j]),
+                    None => paeth(0, previous[i], 0),
+                };
+                row[i] = row[i].wrapping_add(predictor);
+            }
+        }
+    }
+}
+
+/// Undo the filters on `rows` scanlines of `row_bytes` bytes each, every
+/// one prefixed with its filter type byte. Returns the unfiltered scanlines
+/// without their filter type bytes. The size is checked against `data`
+/// before anything is allocated.
+pub fn unfilter(
+    data: &[u8],
+    row_bytes: usize,
+    rows: usize,
+    bytes_per_pixel: usize,
+) -> Result<Vec<u8>, PngError> {
+    let expected = row_bytes
+        .checked_add(1)
+        .and_then(|length| length.checked_mul(rows))
+        .ok_or_else(|| {
+            invalid(format!(
+                "{rows} rows of {row_bytes} bytes are too big to unfilter"
+            ))
+        })?;
+    if data.len() != expected {
+        return Err(invalid(format!(
+            "image data is {} bytes, expected {expected}",
+            data.len()
+        )));
+    }
+    let mut pixels = vec![0; row_bytes * rows];
+    let zeros = vec![0; row_bytes];
+    for (row, filtered) in data.chunks_exact(row_bytes + 1).enumerate() {
+        let filter = FilterType::try_from(filtered[0])
+            .map_err(|filter| invalid(format!("unknown filter type {filter} on row {row}")))?;
+        let (done, rest) = pixels.split_at_mut(row * row_bytes);
+        let current = &mut rest[..row_bytes];
+        current.copy_from_slice(&filtered[1..]);
+        let previous = match row {
+            0 => &zeros[..],
+            _ => &done[(row - 1) * row_bytes..],
+        };
+        unfilter_row(filter, current, previous, bytes_per_pixel);
+    }
+    Ok(pixels)
+}
+
+fn invalid(reason: String) -> PngError {
+    PngError::InvalidImage {
+        reason,
+        offset: 0,
+        index: 0,
+    }
+}
@@ -0,0 +1,113 @@
+use super::*;
+
+#[test]
+fn test_filter_type_from_byte() {
+    for (byte, filter) in FilterType::ALL.iter().enumerate() {
+        assert_eq!(FilterType::try_from(byte as u8), Ok(*filter));
+    }
+    assert_eq!(FilterType::try_from(5), Err(5));
+}
+
+#[test]
+fn test_paeth() {
+    assert_eq!(paeth(10, 20, 10), 20);
+    assert_eq!(paeth(20, 10, 10), 20);
+    assert_eq!(paeth(10, 10, 10), 10);
+    assert_eq!(paeth(0, 255, 128), 128);
+    assert_eq!(paeth(100, 50, 200), 50);
+}
+
+#[test]
+fn test_unfilter_row() {
+    let previous = [10, 20, 30, 40];
+    let cases = [
+        (FilterType::None, [1, 2, 3, 4]),
+        (FilterType::Sub, [1, 2, 4, 6]),
+        (FilterType::Up, [11, 22, 33, 44]),
+        (FilterType::Average, [6, 12, 21, 30]),
+        (FilterType::Paeth, [11, 22, 33, 44]),
+    ];
+    for (filter, expected) in cases {
+        let mut row = [1, 2, 3, 4];
+        unfilter_row(filter, &mut row, &previous, 2);
+        assert_eq!(row, expected, "{filter}");
+    }
+}
+
+#[test]
+fn test_unfilter_wraps() {
+    let mut row = [200, 100];
+    unfilter_row(FilterType::Sub, &mut row, &[0, 0], 1);
+    assert_eq!(row, [200, 44]);
+}
+
+#[test]
+fn test_unfilter() {
+    let data = [0, 1, 2, 3, 2, 1, 1, 1, 1, 1, 1, 1];
+    let pixels = unfilter(&data, 3, 3, 1).unwrap();
+    assert_eq!(pixels, [1, 2, 3, 2, 3, 4, 1, 2, 3]);
+}
+
+#[test]
+fn test_unfilter_bad_data() {
+    assert!(matches!(
+        unfilter(&[0, 1, 2], 3, 1, 1),
+        Err(PngError::InvalidImage { .. })
+    ));
+    assert!(matches!(
+        unfilter(&[5, 1, 2, 3], 3, 1, 1),
+        Err(PngError::InvalidImage { .. })
+    ));
+    for (row_bytes, rows) in [(usize::MAX, 1), (usize::MAX / 2, 3)] {
+        assert!(matches!(
+            unfilter(&[0, 1, 2, 3], row_bytes, rows, 1),
+            Err(PngError::InvalidImage { .. })
+        ));
+    }
+}
+
+#[test]
+fn test_filter_row_round_trip() {
+    let previous = [200, 3, 90, 255, 0, 17];
+    let row = [10, 250, 91, 0, 128, 64];
+    for filter in FilterType::ALL {
+        for bytes_per_pixel in [1, 2, 3] {
+            let mut filtered = Vec::new();
+            filter_row(filter, &row, &previous, bytes_per_pixel, &mut filtered);
+            assert_eq!(filtered[0], filter as u8);
+            let mut unfiltered = filtered[1..].to_vec();
+            unfilter_row(filter, &mut unfiltered, &previous, bytes_per_pixel);
+            assert_eq!(
+                unfiltered, row,
+                "{filter} with {bytes_per_pixel} bytes per pixel"
+            );
+        }
+    }
+}
+
+#[test]
+fn test_filter_round_trip() {
+    let raw: Vec<u8> = (0..60).map(|i| (i * i % 251) as u8).collect();
+    let strategies = FilterType::ALL
+        .map(FilterStrategy::Fixed)
+        .into_iter()
+        .chain([FilterStrategy::Adaptive]);
+    for strategy in strategies {
+        let filtered = filter(&raw, 12, 5, 3, strategy);
+        assert_eq!(filtered.len(), 13 * 5);
+        assert_eq!(unfilter(&filtered, 12, 5, 3).unwrap(), raw, "{strategy}");
+    }
+}
+
+#[test]
+fn test_adaptive_choice() {
+    // a smooth gradient is cheapest as differences from the left, and a
+    // copy of the row above as differences from above
+    let gradient: Vec<u8> = (100..116).collect();
+    let raw = [gradient.clone(), vec![7; 16], vec![7; 16]].concat();
+    let filtered = filter(&raw, 16, 3, 1, FilterStrategy::Adaptive);
+    assert_eq!(filtered[0], FilterType::Sub as u8);
+    assert_eq!(filtered[17], FilterType::Sub as u8);
+    assert_eq!(filtered[34], FilterType::Up as u8);
+    assert!(filtered[35..].iter().all(|&byte| byte == 0));
+}
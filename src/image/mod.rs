@@ -0,0 +1,304 @@
+//! Decoding the pixels of a png. The IDAT chunks are joined into a single
+//! zlib stream, inflated, unfiltered one scanline at a time and then
+//! expanded to 8-bit RGBA, whatever the color type and bit depth, using
+//! PLTE for indexed images and tRNS for transparency. See
+//! [Image layout](http://www.libpng.org/pub/png/spec/1.2/PNG-DataRep.html).
+
+#[cfg(test)]
+mod unit_tests;
+
+use crate::{
+    adam7,
+    chunk::Chunk,
+    chunk_type::ChunkType,
+    encoder,
+    error::PngError,
+    filter,
+    image_header::{ColorType, ImageHeader, InterlaceMethod},
+    png::Png,
+    zlib,
+};
+
+/// A decoded image, converted to 8-bit RGBA.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Image {
+    pub width: u32,
+    pub height: u32,
+    /// Color type the image was stored with
+    pub color_type: ColorType,
+    /// Bit depth the image was stored with
+    pub bit_depth: u8,
+    /// Rows of pixels from top to bottom, each pixel 4 bytes of R, G, B, A
+    pixels: Vec<u8>,
+}
+
+impl Image {
+    /// Number of bytes making up one decoded pixel
+    pub const BYTES_PER_PIXEL: usize = 4;
+
+    /// Decode the image data of a png
+    pub fn decode(png: &Png) -> Result<Image, PngError> {
+        let (header, raw) = scanlines(png)?;
+        let first = find(png, ChunkType::IDAT).unwrap_or_default();
+        // problems with PLTE or tRNS are already reported at those chunks
+        Image::from_scanlines(png, &header, &raw).map_err(|err| match err.offset() {
+            0 => err.at(png.chunk_offset(first), first),
+            _ => err,
+        })
+    }
+    /// An image made of 8-bit RGBA pixels, row by row from the top left
+    pub fn from_rgba(width: u32, height: u32, pixels: Vec<u8>) -> Result<Image, PngError> {
+        let expected = u64::from(width) * u64::from(height) * Self::BYTES_PER_PIXEL as u64;
+        if pixels.len() as u64 != expected {
+            return Err(invalid(format!(
+                "pixel buffer is {} bytes, expected {expected} for a {width}x{height} image",
+                pixels.len()
+            )));
+        }
+        Ok(Image {
+            width,
+            height,
+            color_type: ColorType::Rgba,
+            bit_depth: 8,
+            pixels,
+        })
+    }
+    /// Encode as an 8-bit RGBA png
+    pub fn to_png(&self) -> Result<Png, PngError> {
+        encoder::encode(self.width, self.height, ColorType::Rgba, 8, &self.pixels)
+    }
+    /// Convert scanlines laid out as `header` describes to RGBA, using the
+    /// palette and transparency of `png`. The header may be for part of
+    /// the png, such as an animation frame.
+    pub fn from_scanlines(png: &Png, header: &ImageHeader, raw: &[u8]) -> Result<Image, PngError> {
+        let row_bytes = header.row_bytes(header.width);
+        let rows = header.height as usize;
+        if row_bytes.checked_mul(rows) != Some(raw.len()) {
+            return Err(invalid(format!(
+                "scanlines are {} bytes, expected {rows} rows of {row_bytes} for a {header} image",
+                raw.len()
+            )));
+        }
+        let palette = palette(png, header)?;
+        let key = transparent_key(png, header)?;
+        // at least one bit of the scanlines per pixel, so this is at most 32
+        // times their length
+        let mut pixels = Vec::with_capacity(header.width as usize * rows * Self::BYTES_PER_PIXEL);
+        for row in raw.chunks_exact(row_bytes) {
+            let mut samples = samples(row, header.bit_depth);
+            let mut next = || samples.next().expect("row has a sample for every pixel");
+            let depth = header.bit_depth;
+            for _ in 0..header.width {
+                let pixel = match header.color_type {
+                    ColorType::Grayscale => {
+                        let gray = next();
+                        let alpha = opacity(key == Some([gray; 3]));
+                        let value = scale(gray, depth);
+                        [value, value, value, alpha]
+                    }
+                    ColorType::Rgb => {
+                        let rgb = [next(), next(), next()];
+                        let alpha = opacity(key == Some(rgb));
+                        [
+                            scale(rgb[0], depth),
+                            scale(rgb[1], depth),
+                            scale(rgb[2], depth),
+                            alpha,
+                        ]
+                    }
+                    ColorType::Indexed => {
+                        let index = next();
+                        *palette.get(usize::from(index)).ok_or_else(|| {
+                            invalid(format!(
+                                "palette index {index} is outside the {} entry palette",
+                                palette.len()
+                            ))
+                        })?
+                    }
+                    ColorType::GrayscaleAlpha => {
+                        let value = scale(next(), depth);
+                        [value, value, value, scale(next(), depth)]
+                    }
+                    ColorType::Rgba => [
+                        scale(next(), depth),
+                        scale(next(), depth),
+                        scale(next(), depth),
+                        scale(next(), depth),
+                    ],
+                };
+                pixels.extend_from_slice(&pixel);
+            }
+        }
+        Ok(Image {
+            width: header.width,
+            height: header.height,
+            color_type: header.color_type,
+            bit_depth: header.bit_depth,
+            pixels,
+        })
+    }
+    /// Every pixel, row by row from the top left, as R, G, B, A bytes
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+    /// The R, G, B, A bytes of one pixel, if it is inside the image
+    pub fn pixel(&self, x: u32, y: u32) -> Option<[u8; 4]> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        let start = (y as usize * self.width as usize + x as usize) * Self::BYTES_PER_PIXEL;
+        self.pixels[start..start + Self::BYTES_PER_PIXEL]
+            .try_into()
+            .ok()
+    }
+}
+
+/// Inflate and unfilter the image data of a png, deinterlacing it if it
+/// is interlaced. Returns the header along with the scanlines at their
+/// stored bit depth, top to bottom without filter type bytes.
+pub fn scanlines(png: &Png) -> Result<(ImageHeader, Vec<u8>), PngError> {
+    let header = png.header()?;
+    let ihdr = find(png, ChunkType::IHDR).unwrap_or_default();
+    header
+        .data_length()
+        .map_err(|err| err.at(png.chunk_offset(ihdr), ihdr))?;
+    let first = find(png, ChunkType::IDAT).ok_or_else(|| not_found(png, ChunkType::IDAT))?;
+    let compressed: Vec<u8> = png
+        .chunks()
+        .iter()
+        .filter(|chunk| *chunk.chunk_type() == ChunkType::IDAT)
+        .flat_map(|chunk| chunk.data())
+        .copied()
+        .collect();
+    let raw = decode_scanlines(&compressed, &header)
+        .map_err(|err| err.at(png.chunk_offset(first), first))?;
+    Ok((header, raw))
+}
+
+/// Inflate and unfilter a zlib stream of image data laid out as `header`
+/// describes, deinterlacing it if it is interlaced
+pub fn decode_scanlines(compressed: &[u8], header: &ImageHeader) -> Result<Vec<u8>, PngError> {
+    let data = zlib::inflate(compressed, header.data_length()?)?;
+    match header.interlace_method {
+        InterlaceMethod::None => filter::unfilter(
+            &data,
+            header.row_bytes(header.width),
+            header.height as usize,
+            header.bits_per_pixel().div_ceil(8),
+        ),
+        InterlaceMethod::Adam7 => adam7::deinterlace(&data, header),
+    }
+}
+
+/// Split a scanline into samples of `bit_depth` bits. Samples smaller than
+/// a byte are packed from the most significant bit down.
+pub(crate) fn samples(row: &[u8], bit_depth: u8) -> impl Iterator<Item = u16> + '_ {
+    let depth = usize::from(bit_depth);
+    (0..row.len() * 8 / depth).map(move |i| match bit_depth {
+        16 => u16::from_be_bytes([row[2 * i], row[2 * i + 1]]),
+        8 => u16::from(row[i]),
+        _ => {
+            let bit = i * depth;
+            let shift = 8 - depth - bit % 8;
+            u16::from(row[bit / 8] >> shift) & ((1 << depth) - 1)
+        }
+    })
+}
+
+/// Scale a sample of `bit_depth` bits to 8 bits
+fn scale(sample: u16, bit_depth: u8) -> u8 {
+    match bit_depth {
+        16 => (sample >> 8) as u8,
+        8 => sample as u8,
+        _ => (u32::from(sample) * 255 / ((1 << bit_depth) - 1)) as u8,
+    }
+}
+
+fn opacity(transparent: bool) -> u8 {
+    match transparent {
+        true => 0,
+        false => 255,
+    }
+}
+
+/// The RGBA entries of the palette of an indexed image, with the alpha
+/// values from tRNS. Empty for other color types.
+fn palette(png: &Png, header: &ImageHeader) -> Result<Vec<[u8; 4]>, PngError> {
+    if header.color_type != ColorType::Indexed {
+        return Ok(Vec::new());
+    }
+    let index = find(png, ChunkType::PLTE).ok_or_else(|| not_found(png, ChunkType::PLTE))?;
+    let data = png.chunks()[index].data();
+    if data.is_empty() || !data.len().is_multiple_of(3) {
+        return Err(invalid(format!(
+            "palette is {} bytes, expected a non-zero multiple of 3",
+            data.len()
+        ))
+        .at(png.chunk_offset(index), index));
+    }
+    let alphas = find(png, ChunkType::tRNS).map_or(&[][..], |index| png.chunks()[index].data());
+    Ok(data
+        .chunks_exact(3)
+        .enumerate()
+        .map(|(i, rgb)| {
+            [
+                rgb[0],
+                rgb[1],
+                rgb[2],
+                alphas.get(i).copied().unwrap_or(255),
+            ]
+        })
+        .collect())
+}
+
+/// The single gray level or RGB color that tRNS marks as fully
+/// transparent, for grayscale and RGB images. Gray levels are repeated
+/// three times.
+fn transparent_key(png: &Png, header: &ImageHeader) -> Result<Option<[u16; 3]>, PngError> {
+    let Some(index) = find(png, ChunkType::tRNS) else {
+        return Ok(None);
+    };
+    let data = png.chunks()[index].data();
+    let samples: Vec<u16> = data
+        .chunks_exact(2)
+        .map(|sample| u16::from_be_bytes([sample[0], sample[1]]))
+        .collect();
+    let wrong_length = |expected: usize| {
+        invalid(format!(
+            "tRNS is {} bytes, expected {expected} for {} images",
+            data.len(),
+            header.color_type
+        ))
+        .at(png.chunk_offset(index), index)
+    };
+    match header.color_type {
+        ColorType::Grayscale if data.len() == 2 => Ok(Some([samples[0]; 3])),
+        ColorType::Grayscale => Err(wrong_length(2)),
+        ColorType::Rgb if data.len() == 6 => Ok(Some([samples[0], samples[1], samples[2]])),
+        ColorType::Rgb => Err(wrong_length(6)),
+        _ => Ok(None),
+    }
+}
+
+/// Index of the first chunk of this type
+fn find(png: &Png, chunk_type: ChunkType) -> Option<usize> {
+    png.chunks()
+        .iter()
+        .position(|chunk: &Chunk| *chunk.chunk_type() == chunk_type)
+}
+
+fn not_found(png: &Png, chunk_type: ChunkType) -> PngError {
+    PngError::ChunkNotFound {
+        chunk_type: chunk_type.to_string(),
+        offset: png.byte_len(),
+        index: png.chunks().len(),
+    }
+}
+
+fn invalid(reason: String) -> PngError {
+    PngError::InvalidImage {
+        reason,
+        offset: 0,
+        index: 0,
+    }
+}
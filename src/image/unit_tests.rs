@@ -0,0 +1,212 @@
+use super::*;
+use crate::{
+    image_header::InterlaceMethod,
+    test_utils::{chunk, header},
+};
+
+/// A png of this header, extra chunks and filtered scanlines
+fn testing_png(header: ImageHeader, extra: Vec<Chunk>, scanlines: &[u8]) -> Png {
+    let mut chunks = vec![header.to_chunk()];
+    chunks.extend(extra);
+    chunks.push(chunk("IDAT", &zlib::deflate(scanlines, 6)));
+    chunks.push(chunk("IEND", &[]));
+    Png::from_chunks(chunks)
+}
+
+#[test]
+fn test_rgba() {
+    let png = testing_png(
+        header(2, 1, 8, ColorType::Rgba),
+        vec![],
+        &[0, 1, 2, 3, 4, 5, 6, 7, 8],
+    );
+    let image = png.image().unwrap();
+    assert_eq!((image.width, image.height), (2, 1));
+    assert_eq!(image.color_type, ColorType::Rgba);
+    assert_eq!(image.pixels(), [1, 2, 3, 4, 5, 6, 7, 8]);
+    assert_eq!(image.pixel(1, 0), Some([5, 6, 7, 8]));
+    assert_eq!(image.pixel(2, 0), None);
+}
+
+#[test]
+fn test_filtered_rows() {
+    let png = testing_png(
+        header(2, 2, 8, ColorType::Rgb),
+        vec![],
+        &[1, 10, 20, 30, 1, 1, 1, 2, 0, 0, 0, 5, 5, 5],
+    );
+    let image = png.image().unwrap();
+    assert_eq!(image.pixel(0, 0), Some([10, 20, 30, 255]));
+    assert_eq!(image.pixel(1, 0), Some([11, 21, 31, 255]));
+    assert_eq!(image.pixel(0, 1), Some([10, 20, 30, 255]));
+    assert_eq!(image.pixel(1, 1), Some([16, 26, 36, 255]));
+}
+
+#[test]
+fn test_low_bit_depth_grayscale() {
+    let png = testing_png(
+        header(10, 1, 1, ColorType::Grayscale),
+        vec![],
+        &[0, 0b1010_0000, 0b1100_0000],
+    );
+    let image = png.image().unwrap();
+    let grays: Vec<u8> = image.pixels().chunks(4).map(|pixel| pixel[0]).collect();
+    assert_eq!(grays, [255, 0, 255, 0, 0, 0, 0, 0, 255, 255]);
+
+    let png = testing_png(
+        header(3, 1, 2, ColorType::Grayscale),
+        vec![],
+        &[0, 0b0001_1000],
+    );
+    let image = png.image().unwrap();
+    assert_eq!(
+        image.pixels(),
+        [0, 0, 0, 255, 85, 85, 85, 255, 170, 170, 170, 255]
+    );
+}
+
+#[test]
+fn test_sixteen_bit() {
+    let png = testing_png(
+        header(2, 1, 16, ColorType::GrayscaleAlpha),
+        vec![],
+        &[0, 0x12, 0x34, 0xff, 0xff, 0xab, 0xcd, 0x00, 0x01],
+    );
+    let image = png.image().unwrap();
+    assert_eq!(
+        image.pixels(),
+        [0x12, 0x12, 0x12, 0xff, 0xab, 0xab, 0xab, 0]
+    );
+}
+
+#[test]
+fn test_transparent_key() {
+    let png = testing_png(
+        header(2, 1, 16, ColorType::Grayscale),
+        vec![chunk("tRNS", &[0x12, 0x34])],
+        &[0, 0x12, 0x34, 0x12, 0x35],
+    );
+    let image = png.image().unwrap();
+    assert_eq!(image.pixels(), [0x12, 0x12, 0x12, 0, 0x12, 0x12, 0x12, 255]);
+
+    let png = testing_png(
+        header(2, 1, 8, ColorType::Rgb),
+        vec![chunk("tRNS", &[0, 1, 0, 2, 0, 3])],
+        &[0, 1, 2, 3, 1, 2, 4],
+    );
+    let image = png.image().unwrap();
+    assert_eq!(image.pixels(), [1, 2, 3, 0, 1, 2, 4, 255]);
+}
+
+#[test]
+fn test_indexed() {
+    let palette = chunk("PLTE", &[255, 0, 0, 0, 255, 0, 0, 0, 255]);
+    let png = testing_png(
+        header(3, 1, 2, ColorType::Indexed),
+        vec![palette, chunk("tRNS", &[128])],
+        &[0, 0b0001_1000],
+    );
+    let image = png.image().unwrap();
+    assert_eq!(
+        image.pixels(),
+        [255, 0, 0, 128, 0, 255, 0, 255, 0, 0, 255, 255]
+    );
+}
+
+#[test]
+fn test_index_outside_palette() {
+    let palette = chunk("PLTE", &[255, 0, 0]);
+    let png = testing_png(
+        header(2, 1, 8, ColorType::Indexed),
+        vec![palette],
+        &[0, 0, 1],
+    );
+    let err = png.image().err().unwrap();
+    assert!(matches!(err, PngError::InvalidImage { index: 2, .. }));
+}
+
+#[test]
+fn test_missing_palette() {
+    let png = testing_png(header(2, 1, 8, ColorType::Indexed), vec![], &[0, 0, 0]);
+    assert!(matches!(png.image(), Err(PngError::ChunkNotFound { .. })));
+}
+
+#[test]
+fn test_split_image_data() {
+    let compressed = zlib::deflate(&[0, 10, 20, 0, 30, 40], 6);
+    let (first, second) = compressed.split_at(compressed.len() / 2);
+    let png = Png::from_chunks(vec![
+        header(1, 2, 8, ColorType::GrayscaleAlpha).to_chunk(),
+        chunk("IDAT", first),
+        chunk("IDAT", second),
+        chunk("IEND", &[]),
+    ]);
+    let image = png.image().unwrap();
+    assert_eq!(image.pixels(), [10, 10, 10, 20, 30, 30, 30, 40]);
+}
+
+#[test]
+fn test_short_image_data() {
+    let png = testing_png(
+        header(2, 2, 8, ColorType::Grayscale),
+        vec![chunk("gAMA", &[0, 0, 177, 143])],
+        &[0, 1, 2],
+    );
+    let err = png.image().err().unwrap();
+    assert!(matches!(err, PngError::InvalidImage { index: 2, .. }));
+    assert_eq!(err.offset(), png.chunk_offset(2));
+}
+
+#[test]
+fn test_scanlines_of_the_wrong_length() {
+    let png = testing_png(header(1, 1, 8, ColorType::Grayscale), Vec::new(), &[0, 0]);
+    let huge = header(u32::MAX >> 1, u32::MAX >> 1, 16, ColorType::Rgba);
+    for (header, raw) in [
+        (header(2, 2, 8, ColorType::Grayscale), &[1, 2, 3][..]),
+        (huge, &[0; 8]),
+    ] {
+        assert!(matches!(
+            Image::from_scanlines(&png, &header, raw),
+            Err(PngError::InvalidImage { .. })
+        ));
+    }
+}
+
+#[test]
+fn test_maximal_header() {
+    let max = u32::MAX >> 1;
+    let png = testing_png(header(max, max, 16, ColorType::Rgba), Vec::new(), &[0]);
+    assert!(matches!(
+        png.image(),
+        Err(PngError::InvalidHeader {
+            offset: 8,
+            index: 0,
+            ..
+        })
+    ));
+}
+
+#[test]
+fn test_missing_image_data() {
+    let png = Png::from_chunks(vec![
+        header(1, 1, 8, ColorType::Grayscale).to_chunk(),
+        chunk("IEND", &[]),
+    ]);
+    assert!(matches!(png.image(), Err(PngError::ChunkNotFound { .. })));
+}
+
+#[test]
+fn test_interlaced() {
+    let mut interlaced = header(3, 3, 8, ColorType::Grayscale);
+    interlaced.interlace_method = InterlaceMethod::Adam7;
+    // pass 1 holds (0, 0), pass 4 (2, 0), pass 5 the corners of the bottom
+    // row, pass 6 the middle column in two rows and pass 7 the middle row
+    let png = testing_png(
+        interlaced,
+        vec![],
+        &[0, 1, 0, 3, 0, 7, 9, 0, 2, 0, 8, 0, 4, 5, 6],
+    );
+    let image = png.image().unwrap();
+    let grays: Vec<u8> = image.pixels().chunks(4).map(|pixel| pixel[0]).collect();
+    assert_eq!(grays, [1, 2, 3, 4, 5, 6, 7, 8, 9]);
+}
@@ -0,0 +1,237 @@
+//! The IHDR chunk must appear first. It contains:
+//!
+//! | Field              | Size    |
+//! |--------------------|---------|
+//! | Width              | 4 bytes |
+//! | Height             | 4 bytes |
+//! | Bit depth          | 1 byte  |
+//! | Color type         | 1 byte  |
+//! | Compression method | 1 byte  |
+//! | Filter method      | 1 byte  |
+//! | Interlace method   | 1 byte  |
+//!
+//! Width and height give the image dimensions in pixels. They are 4-byte
+//! integers. Zero is an invalid value. The maximum for each is 2^31 - 1 in
+//! order to accommodate languages that have difficulty with unsigned 4-byte
+//! values. See [IHDR](http://www.libpng.org/pub/png/spec/1.2/PNG-Chunks.html#C.IHDR).
+
+#[cfg(test)]
+mod unit_tests;
+
+use std::fmt::Display;
+
+use crate::{adam7, chunk::Chunk, chunk_type::ChunkType, error::PngError};
+
+/// Color type codes represent sums of the following values: 1 (palette used),
+/// 2 (color used), and 4 (alpha channel used).
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ColorType {
+    /// Each pixel is a grayscale sample.
+    Grayscale = 0,
+    /// Each pixel is an R,G,B triple.
+    Rgb = 2,
+    /// Each pixel is a palette index; a PLTE chunk must appear.
+    Indexed = 3,
+    /// Each pixel is a grayscale sample, followed by an alpha sample.
+    GrayscaleAlpha = 4,
+    /// Each pixel is an R,G,B triple, followed by an alpha sample.
+    Rgba = 6,
+}
+
+impl TryFrom<u8> for ColorType {
+    type Error = u8;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(ColorType::Grayscale),
+            2 => Ok(ColorType::Rgb),
+            3 => Ok(ColorType::Indexed),
+            4 => Ok(ColorType::GrayscaleAlpha),
+            6 => Ok(ColorType::Rgba),
+            other => Err(other),
+        }
+    }
+}
+
+impl ColorType {
+    /// Bit depths the spec allows for this color type
+    pub fn allowed_bit_depths(&self) -> &'static [u8] {
+        match self {
+            ColorType::Grayscale => &[1, 2, 4, 8, 16],
+            ColorType::Indexed => &[1, 2, 4, 8],
+            ColorType::Rgb | ColorType::GrayscaleAlpha | ColorType::Rgba => &[8, 16],
+        }
+    }
+    /// Number of samples that make up one pixel
+    pub fn channels(&self) -> usize {
+        match self {
+            ColorType::Grayscale | ColorType::Indexed => 1,
+            ColorType::GrayscaleAlpha => 2,
+            ColorType::Rgb => 3,
+            ColorType::Rgba => 4,
+        }
+    }
+}
+
+impl Display for ColorType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            ColorType::Grayscale => "grayscale",
+            ColorType::Rgb => "rgb",
+            ColorType::Indexed => "indexed",
+            ColorType::GrayscaleAlpha => "grayscale+alpha",
+            ColorType::Rgba => "rgba",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// Transmission order of the image data.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum InterlaceMethod {
+    /// Pixels are stored sequentially from left to right, and scanlines
+    /// sequentially from top to bottom.
+    None = 0,
+    /// Adam7 interlacing, seven passes over the image.
+    Adam7 = 1,
+}
+
+/// The decoded contents of an IHDR chunk.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ImageHeader {
+    pub width: u32,
+    pub height: u32,
+    /// Number of bits per sample or per palette index (not per pixel)
+    pub bit_depth: u8,
+    pub color_type: ColorType,
+    /// Only compression method 0 (deflate/inflate) is defined
+    pub compression_method: u8,
+    /// Only filter method 0 (adaptive filtering with five basic filter
+    /// types) is defined
+    pub filter_method: u8,
+    pub interlace_method: InterlaceMethod,
+}
+
+impl ImageHeader {
+    /// Length of the IHDR chunk data
+    pub const LENGTH: usize = 13;
+
+    /// Number of bits used by one pixel
+    pub fn bits_per_pixel(&self) -> usize {
+        self.color_type.channels() * usize::from(self.bit_depth)
+    }
+    /// Number of bytes in one unfiltered scanline of `width` pixels, not
+    /// counting the filter type byte
+    pub fn row_bytes(&self, width: u32) -> usize {
+        (width as usize * self.bits_per_pixel()).div_ceil(8)
+    }
+    /// Length of the inflated image data: every scanline with its filter
+    /// type byte, across all seven passes when interlaced. The largest
+    /// headers describe more data than fits in a u64, which is an error.
+    pub fn data_length(&self) -> Result<u64, PngError> {
+        match self.interlace_method {
+            InterlaceMethod::None => self.scanlines_length(self.width, self.height),
+            InterlaceMethod::Adam7 => adam7::pass_sizes(self)?
+                .iter()
+                .try_fold(0u64, |total, pass| total.checked_add(pass.data_length))
+                .ok_or_else(|| self.too_big()),
+        }
+    }
+    /// Length of `height` filtered scanlines of `width` pixels
+    pub(crate) fn scanlines_length(&self, width: u32, height: u32) -> Result<u64, PngError> {
+        (self.row_bytes(width) as u64)
+            .checked_add(1)
+            .and_then(|length| length.checked_mul(u64::from(height)))
+            .ok_or_else(|| self.too_big())
+    }
+    fn too_big(&self) -> PngError {
+        PngError::InvalidHeader {
+            reason: format!("the image data of a {self} image is too big to count in bytes"),
+            offset: 0,
+            index: 0,
+        }
+    }
+    /// The 13 bytes of IHDR chunk data for this header
+    pub fn as_bytes(&self) -> [u8; ImageHeader::LENGTH] {
+        let mut bytes = [0; ImageHeader::LENGTH];
+        bytes[0..4].copy_from_slice(&self.width.to_be_bytes());
+        bytes[4..8].copy_from_slice(&self.height.to_be_bytes());
+        bytes[8] = self.bit_depth;
+        bytes[9] = self.color_type as u8;
+        bytes[10] = self.compression_method;
+        bytes[11] = self.filter_method;
+        bytes[12] = self.interlace_method as u8;
+        bytes
+    }
+    /// Build the IHDR chunk holding this header
+    pub fn to_chunk(&self) -> Chunk {
+        Chunk::new(ChunkType::IHDR, self.as_bytes().to_vec())
+    }
+}
+
+impl TryFrom<&[u8]> for ImageHeader {
+    type Error = PngError;
+
+    /// Parse and validate the data of an IHDR chunk
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        let invalid = |reason: String| PngError::InvalidHeader {
+            reason,
+            offset: 0,
+            index: 0,
+        };
+        let bytes: [u8; ImageHeader::LENGTH] = value
+            .try_into()
+            .map_err(|_| invalid(format!("length is {}, expected 13", value.len())))?;
+
+        let width = u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+        let height = u32::from_be_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]);
+        for (name, value) in [("width", width), ("height", height)] {
+            if value == 0 || value > Chunk::MAX_LENGTH {
+                return Err(invalid(format!("{name} {value} is outside 1..=2^31 - 1")));
+            }
+        }
+        let color_type = ColorType::try_from(bytes[9])
+            .map_err(|color_type| invalid(format!("unknown color type {color_type}")))?;
+        let bit_depth = bytes[8];
+        if !color_type.allowed_bit_depths().contains(&bit_depth) {
+            return Err(invalid(format!(
+                "bit depth {bit_depth} is not allowed for {color_type} images, expected one of {:?}",
+                color_type.allowed_bit_depths()
+            )));
+        }
+        if bytes[10] != 0 {
+            return Err(invalid(format!("unknown compression method {}", bytes[10])));
+        }
+        if bytes[11] != 0 {
+            return Err(invalid(format!("unknown filter method {}", bytes[11])));
+        }
+        let interlace_method = match bytes[12] {
+            0 => InterlaceMethod::None,
+            1 => InterlaceMethod::Adam7,
+            other => return Err(invalid(format!("unknown interlace method {other}"))),
+        };
+        Ok(ImageHeader {
+            width,
+            height,
+            bit_depth,
+            color_type,
+            compression_method: bytes[10],
+            filter_method: bytes[11],
+            interlace_method,
+        })
+    }
+}
+
+impl Display for ImageHeader {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}x{}, {}-bit {}",
+            self.width, self.height, self.bit_depth, self.color_type
+        )?;
+        if self.interlace_method == InterlaceMethod::Adam7 {
+            write!(f, ", interlaced")?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,108 @@
+use super::*;
+
+fn testing_header() -> ImageHeader {
+    ImageHeader {
+        width: 50,
+        height: 40,
+        bit_depth: 8,
+        color_type: ColorType::Rgba,
+        compression_method: 0,
+        filter_method: 0,
+        interlace_method: InterlaceMethod::None,
+    }
+}
+
+#[test]
+fn test_header_from_bytes() {
+    let bytes = [0, 0, 0, 50, 0, 0, 0, 40, 8, 6, 0, 0, 0];
+    let header = ImageHeader::try_from(&bytes[..]).unwrap();
+    assert_eq!(header, testing_header());
+}
+
+#[test]
+fn test_header_round_trip() {
+    let header = testing_header();
+    let chunk = header.to_chunk();
+    assert_eq!(chunk.chunk_type().to_string(), "IHDR");
+    assert_eq!(ImageHeader::try_from(chunk.data()).unwrap(), header);
+}
+
+#[test]
+fn test_invalid_length() {
+    let bytes = testing_header().as_bytes();
+    assert!(ImageHeader::try_from(&bytes[..12]).is_err());
+}
+
+#[test]
+fn test_zero_dimensions() {
+    let mut bytes = testing_header().as_bytes();
+    bytes[0..4].copy_from_slice(&[0, 0, 0, 0]);
+    assert!(ImageHeader::try_from(&bytes[..]).is_err());
+}
+
+#[test]
+fn test_color_type_bit_depth_combinations() {
+    let mut bytes = testing_header().as_bytes();
+    for color_type in [0, 2, 3, 4, 6] {
+        for bit_depth in [1, 2, 4, 8, 16] {
+            bytes[8] = bit_depth;
+            bytes[9] = color_type;
+            let allowed = match color_type {
+                0 => true,
+                3 => bit_depth <= 8,
+                _ => bit_depth >= 8,
+            };
+            assert_eq!(
+                ImageHeader::try_from(&bytes[..]).is_ok(),
+                allowed,
+                "color type {color_type} bit depth {bit_depth}"
+            );
+        }
+    }
+    bytes[9] = 1;
+    assert!(ImageHeader::try_from(&bytes[..]).is_err());
+}
+
+#[test]
+fn test_unknown_methods() {
+    for position in [10, 11, 12] {
+        let mut bytes = testing_header().as_bytes();
+        bytes[position] = 2;
+        assert!(matches!(
+            ImageHeader::try_from(&bytes[..]),
+            Err(PngError::InvalidHeader { .. })
+        ));
+    }
+}
+
+#[test]
+fn test_row_bytes() {
+    let mut header = testing_header();
+    assert_eq!(header.row_bytes(50), 200);
+    header.color_type = ColorType::Grayscale;
+    header.bit_depth = 1;
+    assert_eq!(header.row_bytes(50), 7);
+}
+
+#[test]
+fn test_data_length() {
+    let mut header = testing_header();
+    assert_eq!(header.data_length().unwrap(), (50 * 4 + 1) * 40);
+    header.interlace_method = InterlaceMethod::Adam7;
+    assert!(header.data_length().unwrap() > (50 * 4 + 1) * 40);
+}
+
+#[test]
+fn test_maximal_data_length() {
+    for interlace_method in [0, 1] {
+        let mut bytes = [
+            0x7f, 0xff, 0xff, 0xff, 0x7f, 0xff, 0xff, 0xff, 16, 6, 0, 0, 0,
+        ];
+        bytes[12] = interlace_method;
+        let header = ImageHeader::try_from(&bytes[..]).unwrap();
+        assert!(matches!(
+            header.data_length(),
+            Err(PngError::InvalidHeader { .. })
+        ));
+    }
+}
@@ -0,0 +1,236 @@
+//! A chunk by chunk account of a png for working out why it misbehaves:
+//! where each chunk is, whether its CRC is right and, for chunk types that
+//! are understood here, what it says.
+
+#[cfg(test)]
+mod unit_tests;
+
+use std::fmt::{Display, Write};
+
+use crate::{
+    apng::{AnimationControl, FrameControl},
+    chunk::Chunk,
+    chunk_type::ChunkType,
+    error::PngError,
+    image_header::ImageHeader,
+    png::Png,
+    text::TextChunk,
+};
+
+/// Text longer than this many characters is cut short in summaries
+const TEXT_PREVIEW_LENGTH: usize = 40;
+
+/// Whether the CRC stored with a chunk matches its contents.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CrcStatus {
+    Ok,
+    Mismatch { stored: u32, calculated: u32 },
+}
+
+impl Display for CrcStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CrcStatus::Ok => write!(f, "ok"),
+            CrcStatus::Mismatch { stored, calculated } => {
+                write!(
+                    f,
+                    "bad, stored {stored:08x} but calculated {calculated:08x}"
+                )
+            }
+        }
+    }
+}
+
+/// What is known about one chunk of a png.
+pub struct ChunkReport {
+    pub index: usize,
+    /// Byte position of the start of the chunk in the file
+    pub offset: u64,
+    /// Length of the chunk data
+    pub length: u32,
+    pub chunk_type: ChunkType,
+    pub crc: CrcStatus,
+    /// The decoded contents of a known chunk type, or why they couldn't be
+    /// decoded
+    pub summary: Result<Option<String>, PngError>,
+}
+
+/// Report on every chunk of a png, in file order
+pub fn inspect(png: &Png) -> Vec<ChunkReport> {
+    let mut next_offset = Png::STANDARD_HEADER.len() as u64;
+    png.chunks()
+        .iter()
+        .enumerate()
+        .map(|(index, chunk)| {
+            let offset = next_offset;
+            next_offset += u64::from(chunk.length()) + 12;
+            let crc = match chunk.written_crc() == chunk.crc() {
+                true => CrcStatus::Ok,
+                false => CrcStatus::Mismatch {
+                    stored: chunk.written_crc(),
+                    calculated: chunk.crc(),
+                },
+            };
+            ChunkReport {
+                index,
+                offset,
+                length: chunk.length(),
+                chunk_type: *chunk.chunk_type(),
+                crc,
+                summary: summary(chunk).map_err(|err| err.at(offset, index)),
+            }
+        })
+        .collect()
+}
+
+/// Why parsing stopped before reaching IEND, if it did: the error from the
+/// chunk where the bytes left over as trailing bytes start. Trailing bytes
+/// after IEND are not an error.
+pub fn tail_error(png: &Png) -> Option<PngError> {
+    let ended = png
+        .chunks()
+        .last()
+        .is_some_and(|chunk| *chunk.chunk_type() == ChunkType::IEND);
+    if ended || png.trailing_bytes().is_empty() {
+        return None;
+    }
+    let index = png.chunks().len();
+    Chunk::parse_raw(png.trailing_bytes())
+        .err()
+        .map(|err| err.at(png.chunk_offset(index), index))
+}
+
+/// A short description of what a chunk says, for the chunk types that are
+/// understood here. Other chunk types have no summary.
+pub fn summary(chunk: &Chunk) -> Result<Option<String>, PngError> {
+    let data = chunk.data();
+    let invalid = |reason: String| PngError::InvalidChunk {
+        chunk_type: chunk.chunk_type().to_string(),
+        reason,
+        offset: 0,
+        index: 0,
+    };
+    let expect_length = |length: usize| match data.len() == length {
+        true => Ok(()),
+        false => Err(invalid(format!(
+            "data is {} bytes, expected {length}",
+            data.len()
+        ))),
+    };
+    let summary = match *chunk.chunk_type() {
+        ChunkType::IHDR => ImageHeader::try_from(data)?.to_string(),
+        ChunkType::PLTE => {
+            if data.is_empty() || !data.len().is_multiple_of(3) {
+                return Err(invalid(format!(
+                    "data is {} bytes, not a non-zero multiple of 3",
+                    data.len()
+                )));
+            }
+            format!("{} colors", data.len() / 3)
+        }
+        ChunkType::gAMA => {
+            expect_length(4)?;
+            format!("gamma {:.5}", f64::from(word(data, 0)) / 100_000.0)
+        }
+        ChunkType::cHRM => {
+            expect_length(32)?;
+            format!(
+                "white point {:.4}, {:.4}",
+                f64::from(word(data, 0)) / 100_000.0,
+                f64::from(word(data, 4)) / 100_000.0
+            )
+        }
+        ChunkType::sRGB => {
+            expect_length(1)?;
+            let intent = match data[0] {
+                0 => "perceptual",
+                1 => "relative colorimetric",
+                2 => "saturation",
+                3 => "absolute colorimetric",
+                other => return Err(invalid(format!("unknown rendering intent {other}"))),
+            };
+            format!("{intent} rendering intent")
+        }
+        ChunkType::iCCP => {
+            let name = data
+                .iter()
+                .position(|&byte| byte == 0)
+                .map(|end| &data[..end])
+                .ok_or_else(|| invalid("no null separator after the profile name".to_string()))?;
+            format!("profile {:?}", String::from_utf8_lossy(name))
+        }
+        ChunkType::pHYs => {
+            expect_length(9)?;
+            let (x, y) = (word(data, 0), word(data, 4));
+            match data[8] {
+                0 => format!("{x}x{y} pixels per unit, aspect ratio only"),
+                1 => format!("{x}x{y} pixels per metre"),
+                other => return Err(invalid(format!("unknown unit {other}"))),
+            }
+        }
+        ChunkType::tIME => {
+            expect_length(7)?;
+            format!(
+                "{}-{:02}-{:02} {:02}:{:02}:{:02} UTC",
+                u16::from_be_bytes([data[0], data[1]]),
+                data[2],
+                data[3],
+                data[4],
+                data[5],
+                data[6]
+            )
+        }
+        ChunkType::tEXt | ChunkType::zTXt | ChunkType::iTXt => {
+            let text = TextChunk::try_from(chunk)?;
+            let mut preview: String = text
+                .text
+                .chars()
+                .take(TEXT_PREVIEW_LENGTH)
+                .map(|c| if c.is_control() { ' ' } else { c })
+                .collect();
+            if text.text.chars().count() > TEXT_PREVIEW_LENGTH {
+                preview.push_str("...");
+            }
+            format!("{}: {preview}", text.keyword)
+        }
+        ChunkType::acTL => AnimationControl::try_from(data)?.to_string(),
+        ChunkType::fcTL => {
+            let frame = FrameControl::try_from(data)?;
+            format!("sequence {}, {frame}", frame.sequence_number)
+        }
+        ChunkType::fdAT => {
+            if data.len() < 4 {
+                return Err(invalid("too short for a sequence number".to_string()));
+            }
+            format!("sequence {}", word(data, 0))
+        }
+        _ => return Ok(None),
+    };
+    Ok(Some(summary))
+}
+
+/// Format bytes 16 to a line, as hex and as ASCII, showing at most `limit`
+/// bytes
+pub fn hex_dump(data: &[u8], limit: Option<usize>) -> String {
+    let shown = &data[..limit.map_or(data.len(), |limit| limit.min(data.len()))];
+    let mut dump = String::new();
+    for (line, bytes) in shown.chunks(16).enumerate() {
+        let hex: Vec<String> = bytes.iter().map(|byte| format!("{byte:02x}")).collect();
+        let ascii: String = bytes
+            .iter()
+            .map(|&byte| match byte.is_ascii_graphic() || byte == b' ' {
+                true => byte as char,
+                false => '.',
+            })
+            .collect();
+        let _ = writeln!(dump, "{:08x}  {:<47}  |{ascii}|", line * 16, hex.join(" "));
+    }
+    if shown.len() < data.len() {
+        let _ = writeln!(dump, "... {} more bytes", data.len() - shown.len());
+    }
+    dump
+}
+
+fn word(bytes: &[u8], at: usize) -> u32 {
+    u32::from_be_bytes([bytes[at], bytes[at + 1], bytes[at + 2], bytes[at + 3]])
+}
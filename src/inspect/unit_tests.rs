@@ -0,0 +1,121 @@
+use super::*;
+use crate::test_utils::chunk;
+
+fn summarize(chunk_type: &str, data: &[u8]) -> Option<String> {
+    summary(&chunk(chunk_type, data)).unwrap()
+}
+
+#[test]
+fn test_summaries() {
+    assert_eq!(
+        summarize("IHDR", &[0, 0, 0, 50, 0, 0, 0, 40, 8, 6, 0, 0, 1]).as_deref(),
+        Some("50x40, 8-bit rgba, interlaced")
+    );
+    assert_eq!(
+        summarize("gAMA", &[0, 0, 177, 143]).as_deref(),
+        Some("gamma 0.45455")
+    );
+    assert_eq!(
+        summarize("sRGB", &[0]).as_deref(),
+        Some("perceptual rendering intent")
+    );
+    assert_eq!(
+        summarize("pHYs", &[0, 0, 14, 195, 0, 0, 14, 195, 1]).as_deref(),
+        Some("3779x3779 pixels per metre")
+    );
+    assert_eq!(
+        summarize("tIME", &[7, 234, 10, 16, 9, 5, 0]).as_deref(),
+        Some("2026-10-16 09:05:00 UTC")
+    );
+    assert_eq!(
+        summarize("tEXt", b"Title\0Dice").as_deref(),
+        Some("Title: Dice")
+    );
+    assert_eq!(summarize("PLTE", &[0; 9]).as_deref(), Some("3 colors"));
+    assert_eq!(summarize("IDAT", &[1, 2, 3]), None);
+    assert_eq!(summarize("ruSt", b"secret"), None);
+}
+
+#[test]
+fn test_long_text_is_cut_short() {
+    let text = format!("Comment\0{}", "a\n".repeat(30));
+    let summary = summarize("tEXt", text.as_bytes()).unwrap();
+    assert_eq!(summary, format!("Comment: {}...", "a ".repeat(20)));
+}
+
+#[test]
+fn test_inspect() {
+    let mut bad = chunk("gAMA", &[0, 0, 177, 143]);
+    bad = Chunk::with_raw_crc(*bad.chunk_type(), bad.data().to_vec(), 7);
+    let png = Png::from_chunks(vec![
+        chunk("IHDR", &[0, 0, 0, 1, 0, 0, 0, 1, 8, 0, 0, 0, 0]),
+        bad,
+        chunk("sRGB", &[9]),
+        chunk("IEND", &[]),
+    ]);
+    let reports = inspect(&png);
+    assert_eq!(reports.len(), 4);
+    assert_eq!(
+        (reports[1].index, reports[1].offset, reports[1].length),
+        (1, 33, 4)
+    );
+    assert_eq!(reports[0].crc, CrcStatus::Ok);
+    assert_eq!(
+        reports[1].crc,
+        CrcStatus::Mismatch {
+            stored: 7,
+            calculated: png.chunks()[1].crc()
+        }
+    );
+    assert_eq!(
+        reports[1].summary.as_ref().unwrap().as_deref(),
+        Some("gamma 0.45455")
+    );
+
+    let err = reports[2].summary.as_ref().err().unwrap();
+    assert_eq!((err.offset(), err.index()), (49, 2));
+    assert_eq!(
+        err.to_string(),
+        "invalid sRGB chunk 2 at byte 49: unknown rendering intent 9"
+    );
+}
+
+#[test]
+fn test_tail_error() {
+    let png = Png::from_chunks(vec![
+        chunk("IHDR", &[0, 0, 0, 1, 0, 0, 0, 1, 8, 0, 0, 0, 0]),
+        chunk("tEXt", b"Title\0Dice"),
+        chunk("IEND", &[]),
+    ]);
+    let mut bytes = png.as_bytes();
+    assert!(tail_error(&Png::from_bytes_raw(&bytes).unwrap()).is_none());
+    bytes.extend_from_slice(b"after the end");
+    assert!(tail_error(&Png::from_bytes_raw(&bytes).unwrap()).is_none());
+
+    bytes[37..41].copy_from_slice(b"t3Xt");
+    let corrupt = Png::from_bytes_raw(&bytes).unwrap();
+    assert_eq!(corrupt.chunks().len(), 1);
+    let err = tail_error(&corrupt).unwrap();
+    assert!(matches!(err, PngError::InvalidChunkType { .. }), "{err}");
+    assert_eq!((err.offset(), err.index()), (33, 1));
+}
+
+#[test]
+fn test_wrong_length() {
+    let err = summary(&chunk("gAMA", &[1, 2])).err().unwrap();
+    assert!(err.to_string().contains("data is 2 bytes, expected 4"));
+}
+
+#[test]
+fn test_hex_dump() {
+    let data: Vec<u8> = (0x40..0x54).collect();
+    assert_eq!(
+        hex_dump(&data, None),
+        "00000000  40 41 42 43 44 45 46 47 48 49 4a 4b 4c 4d 4e 4f  |@ABCDEFGHIJKLMNO|\n\
+         00000010  50 51 52 53                                      |PQRS|\n"
+    );
+    assert_eq!(
+        hex_dump(&[0, b'a', 0xff], Some(2)),
+        "00000000  00 61                                            |.a|\n... 1 more bytes\n"
+    );
+}
@@ -0,0 +1,31 @@
+//! Read, edit and write PNG files at the chunk level.
+//!
+//! Implementing the [PNG Spec](http://www.libpng.org/pub/png/spec/1.2/PNG-Structure.html)
+
+pub mod adam7;
+pub mod apng;
+pub mod chunk;
+pub mod chunk_type;
+pub mod crypto;
+pub mod diff;
+pub mod dump;
+pub mod encoder;
+pub mod error;
+pub mod filter;
+pub mod image;
+pub mod image_header;
+pub mod inspect;
+pub mod lsb;
+pub mod optimize;
+pub mod payload;
+pub mod png;
+pub mod reader;
+pub mod recover;
+pub mod registry;
+pub mod strip;
+#[cfg(test)]
+mod test_utils;
+pub mod text;
+pub mod validate;
+pub mod writer;
+pub mod zlib;
@@ -0,0 +1,253 @@
+//! Hiding messages in the least significant bits of pixel samples instead
+//! of in a chunk of their own. The image is decoded, one bit of the message
+//! replaces the lowest bit of each sample used, and the image is encoded
+//! again. Nothing changes but the image data, so the file has the same
+//! chunks as before.
+//!
+//! The hidden bits are a 4 byte tag, a 4 byte big endian message length and
+//! then the message. The tag is a chunk type, so that the same name is used
+//! to find a message whichever way it was hidden. Bits are taken a byte at a
+//! time from the most significant down, and go either into consecutive
+//! samples or into samples picked by a pseudo random generator seeded from a
+//! key.
+//!
+//! Only 8 and 16-bit images without a palette are supported: changing the
+//! lowest bit of a palette index or of a 1, 2 or 4-bit sample can change a
+//! pixel completely. For 16-bit samples only the low byte is changed.
+//! Images with a tRNS chunk are refused too, since changing a bit could make
+//! an opaque pixel the transparent key color or the other way around.
+
+#[cfg(test)]
+mod unit_tests;
+
+use std::collections::HashMap;
+
+use rand_chacha::{
+    rand_core::{RngCore, SeedableRng},
+    ChaCha20Rng,
+};
+
+use crate::{
+    chunk_type::ChunkType,
+    crypto::{self, KdfParams},
+    error::PngError,
+    image,
+    image_header::{ColorType, ImageHeader},
+    png::Png,
+};
+
+/// Number of bytes hidden before the message: the tag and the length
+pub const HEADER_LENGTH: usize = 8;
+
+/// Salt used when turning a key into a seed. Keys only choose positions and
+/// the same key has to give the same positions in every file, so the salt
+/// is fixed.
+const KEY_SALT: &[u8] = b"pngame lsb positions";
+
+/// Which samples the bits of a message go into.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum BitOrder {
+    /// One bit per sample, starting from the top left
+    Sequential,
+    /// Samples chosen by a ChaCha20 generator with this seed, never using
+    /// the same sample twice
+    Keyed([u8; 32]),
+}
+
+impl BitOrder {
+    /// Scatter bits over samples chosen with a key. The key is stretched
+    /// with Argon2id, so this takes a moment.
+    pub fn from_key(key: &str) -> Result<BitOrder, PngError> {
+        crypto::derive_key(key, KEY_SALT, KdfParams::default()).map(BitOrder::Keyed)
+    }
+    /// The sample indices to use, out of `samples`, in order
+    fn positions(&self, samples: u64) -> Positions {
+        let rng = match self {
+            BitOrder::Sequential => None,
+            BitOrder::Keyed(seed) => Some(ChaCha20Rng::from_seed(*seed)),
+        };
+        Positions {
+            samples,
+            taken: 0,
+            rng,
+            swapped: HashMap::new(),
+        }
+    }
+}
+
+/// Sample indices without repeats. Keyed orders are a Fisher-Yates shuffle
+/// of every index done lazily, one position at a time, so that only the
+/// positions actually used are kept in memory.
+struct Positions {
+    samples: u64,
+    taken: u64,
+    rng: Option<ChaCha20Rng>,
+    /// Entries of the shuffled array that are no longer their own index
+    swapped: HashMap<u64, u64>,
+}
+
+impl Iterator for Positions {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        if self.taken == self.samples {
+            return None;
+        }
+        let i = self.taken;
+        self.taken += 1;
+        let Some(rng) = &mut self.rng else {
+            return Some(i);
+        };
+        // pick j in i..samples, scaling rather than taking a remainder so
+        // that every index is as likely as any other
+        let remaining = u128::from(self.samples - i);
+        let j = i + ((u128::from(rng.next_u64()) * remaining) >> 64) as u64;
+        let at_j = self.swapped.get(&j).copied().unwrap_or(j);
+        let at_i = self.swapped.remove(&i).unwrap_or(i);
+        if j != i {
+            self.swapped.insert(j, at_i);
+        }
+        Some(at_j)
+    }
+}
+
+/// Where the usable samples are in the scanlines of an image.
+struct Layout {
+    /// Total number of samples in the image
+    samples: u64,
+    samples_per_row: u64,
+    row_bytes: u64,
+    bytes_per_sample: u64,
+}
+
+impl Layout {
+    fn new(header: &ImageHeader) -> Result<Layout, PngError> {
+        if header.color_type == ColorType::Indexed || header.bit_depth < 8 {
+            return Err(PngError::InvalidImage {
+                reason: format!("can't hide data in the pixels of a {header} image"),
+                offset: 0,
+                index: 0,
+            });
+        }
+        let samples_per_row = u64::from(header.width) * header.color_type.channels() as u64;
+        Ok(Layout {
+            samples: samples_per_row * u64::from(header.height),
+            samples_per_row,
+            row_bytes: header.row_bytes(header.width) as u64,
+            bytes_per_sample: u64::from(header.bit_depth / 8),
+        })
+    }
+    /// Index into the scanlines of the lowest byte of a sample
+    fn byte(&self, sample: u64) -> usize {
+        let row = sample / self.samples_per_row;
+        let column = sample % self.samples_per_row;
+        (row * self.row_bytes + (column + 1) * self.bytes_per_sample - 1) as usize
+    }
+    /// Longest message that fits, in bytes
+    fn capacity(&self) -> u64 {
+        (self.samples / 8).saturating_sub(HEADER_LENGTH as u64)
+    }
+}
+
+/// Number of message bytes that can be hidden in the pixels of a png
+pub fn capacity(png: &Png) -> Result<u64, PngError> {
+    refuse_transparency(png)?;
+    Ok(Layout::new(&png.header()?)?.capacity())
+}
+
+/// Hide a message in the pixels of a png, under a tag
+pub fn hide(
+    png: &mut Png,
+    tag: ChunkType,
+    message: &[u8],
+    order: &BitOrder,
+) -> Result<(), PngError> {
+    refuse_transparency(png)?;
+    let (header, mut raw) = image::scanlines(png)?;
+    let layout = Layout::new(&header)?;
+    let capacity = layout.capacity();
+    let length = u32::try_from(message.len())
+        .ok()
+        .filter(|&length| u64::from(length) <= capacity)
+        .ok_or(PngError::MessageTooLarge {
+            length: message.len() as u64,
+            capacity,
+            offset: 0,
+            index: 0,
+        })?;
+
+    let bits = tag
+        .bytes()
+        .into_iter()
+        .chain(length.to_be_bytes())
+        .chain(message.iter().copied())
+        .flat_map(|byte| (0..8).rev().map(move |bit| (byte >> bit) & 1));
+    for (position, bit) in order.positions(layout.samples).zip(bits) {
+        let byte = &mut raw[layout.byte(position)];
+        *byte = (*byte & !1) | bit;
+    }
+    png.set_scanlines(&header, &raw)
+}
+
+/// Pull a message hidden by [`hide`] back out of the pixels of a png. Fails
+/// if there isn't one with this tag.
+pub fn reveal(png: &Png, tag: ChunkType, order: &BitOrder) -> Result<Vec<u8>, PngError> {
+    let (header, raw) = image::scanlines(png)?;
+    let layout = Layout::new(&header)?;
+    let mut positions = order.positions(layout.samples);
+    let mut read = |length: usize| -> Vec<u8> {
+        (0..length)
+            .map(|_| {
+                (0..8).fold(0, |byte, _| {
+                    let bit = positions
+                        .next()
+                        .map_or(0, |position| raw[layout.byte(position)] & 1);
+                    (byte << 1) | bit
+                })
+            })
+            .collect()
+    };
+    let first = png
+        .chunks()
+        .iter()
+        .position(|chunk| *chunk.chunk_type() == ChunkType::IDAT)
+        .unwrap_or_default();
+    let not_found = |reason: String| PngError::NoHiddenMessage {
+        reason,
+        offset: png.chunk_offset(first),
+        index: first,
+    };
+    if layout.samples < HEADER_LENGTH as u64 * 8 || read(4) != tag.bytes() {
+        return Err(not_found(format!("there is no {tag} tag")));
+    }
+    let length_bytes = read(4);
+    let length = u32::from_be_bytes([
+        length_bytes[0],
+        length_bytes[1],
+        length_bytes[2],
+        length_bytes[3],
+    ]);
+    if u64::from(length) > layout.capacity() {
+        return Err(not_found(format!(
+            "{tag} message claims to be {length} bytes, more than the pixels can hold"
+        )));
+    }
+    Ok(read(length as usize))
+}
+
+/// Fail if the image has a tRNS chunk, whose transparent key color a
+/// changed bit could turn an opaque pixel into
+fn refuse_transparency(png: &Png) -> Result<(), PngError> {
+    match png
+        .chunks()
+        .iter()
+        .position(|chunk| *chunk.chunk_type() == ChunkType::tRNS)
+    {
+        Some(index) => Err(PngError::InvalidImage {
+            reason: "can't hide data in the pixels of an image with a tRNS chunk".to_string(),
+            offset: png.chunk_offset(index),
+            index,
+        }),
+        None => Ok(()),
+    }
+}
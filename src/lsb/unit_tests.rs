@@ -0,0 +1,137 @@
+use super::*;
+use crate::chunk::Chunk;
+use crate::encoder::{encode, encode_with_options, EncodeOptions};
+use std::str::FromStr;
+
+fn testing_png(width: u32, height: u32, color_type: ColorType, bit_depth: u8) -> Png {
+    let row_bytes = width as usize * color_type.channels() * usize::from(bit_depth) / 8;
+    let pixels: Vec<u8> = (0..row_bytes * height as usize)
+        .map(|i| (i * 37 % 256) as u8)
+        .collect();
+    encode(width, height, color_type, bit_depth, &pixels).unwrap()
+}
+
+fn tag() -> ChunkType {
+    ChunkType::from_str("ruSt").unwrap()
+}
+
+#[test]
+fn test_round_trip() {
+    for order in [BitOrder::Sequential, BitOrder::Keyed([7; 32])] {
+        let mut png = testing_png(20, 20, ColorType::Rgb, 8);
+        hide(&mut png, tag(), b"hidden in plain sight", &order).unwrap();
+        let reread = Png::try_from(png.as_bytes().as_slice()).unwrap();
+        assert_eq!(
+            reveal(&reread, tag(), &order).unwrap(),
+            b"hidden in plain sight"
+        );
+    }
+}
+
+#[test]
+fn test_only_lowest_bits_change() {
+    for bit_depth in [8, 16] {
+        let mut png = testing_png(10, 10, ColorType::Rgba, bit_depth);
+        let (_, before) = image::scanlines(&png).unwrap();
+        let chunks = png.to_string();
+        hide(&mut png, tag(), &[0xa5; 40], &BitOrder::Keyed([1; 32])).unwrap();
+        let (_, after) = image::scanlines(&png).unwrap();
+        assert_eq!(png.to_string(), chunks);
+        assert_ne!(before, after);
+        for (i, (before, after)) in before.iter().zip(&after).enumerate() {
+            match bit_depth == 16 && i % 2 == 0 {
+                true => assert_eq!(before, after),
+                false => assert!(before ^ after <= 1),
+            }
+        }
+    }
+}
+
+#[test]
+fn test_capacity() {
+    let png = testing_png(20, 20, ColorType::Rgb, 8);
+    assert_eq!(capacity(&png).unwrap(), 20 * 20 * 3 / 8 - 8);
+
+    let mut png = testing_png(20, 20, ColorType::Rgb, 8);
+    hide(&mut png, tag(), &[1; 142], &BitOrder::Sequential).unwrap();
+    assert_eq!(
+        reveal(&png, tag(), &BitOrder::Sequential).unwrap(),
+        [1; 142]
+    );
+    let err = hide(&mut png, tag(), &[1; 143], &BitOrder::Sequential)
+        .err()
+        .unwrap();
+    assert!(matches!(
+        err,
+        PngError::MessageTooLarge {
+            length: 143,
+            capacity: 142,
+            ..
+        }
+    ));
+}
+
+#[test]
+fn test_wrong_tag_or_key() {
+    let mut png = testing_png(20, 20, ColorType::GrayscaleAlpha, 16);
+    hide(&mut png, tag(), b"message", &BitOrder::Keyed([3; 32])).unwrap();
+    let other = ChunkType::from_str("ruSx").unwrap();
+    assert!(matches!(
+        reveal(&png, other, &BitOrder::Keyed([3; 32])),
+        Err(PngError::NoHiddenMessage { .. })
+    ));
+    assert!(matches!(
+        reveal(&png, tag(), &BitOrder::Keyed([4; 32])),
+        Err(PngError::NoHiddenMessage { .. })
+    ));
+    assert!(reveal(
+        &testing_png(20, 20, ColorType::Rgb, 8),
+        tag(),
+        &BitOrder::Sequential
+    )
+    .is_err());
+}
+
+#[test]
+fn test_unsupported_formats() {
+    let options = EncodeOptions {
+        palette: vec![[0, 0, 0], [255, 255, 255]],
+        ..Default::default()
+    };
+    let mut indexed = encode_with_options(8, 8, ColorType::Indexed, 8, &[1; 64], &options).unwrap();
+    assert!(matches!(
+        hide(&mut indexed, tag(), b"no", &BitOrder::Sequential),
+        Err(PngError::InvalidImage { .. })
+    ));
+    let grayscale = encode(16, 16, ColorType::Grayscale, 4, &[0; 128]).unwrap();
+    assert!(capacity(&grayscale).is_err());
+}
+
+#[test]
+fn test_transparency_refused() {
+    let mut png = testing_png(8, 8, ColorType::Rgb, 8);
+    let index = png.insert(Chunk::new(ChunkType::tRNS, vec![0, 0, 0, 37, 0, 74]));
+    let before = png.as_bytes();
+    let result = hide(&mut png, tag(), b"no", &BitOrder::Sequential);
+    assert!(
+        matches!(result, Err(PngError::InvalidImage { index: at, .. }) if at == index),
+        "{result:?}"
+    );
+    assert_eq!(png.as_bytes(), before);
+    assert!(capacity(&png).is_err());
+}
+
+#[test]
+fn test_keyed_positions_are_a_permutation() {
+    let mut positions: Vec<u64> = BitOrder::Keyed([9; 32]).positions(100).collect();
+    assert_ne!(positions, (0..100).collect::<Vec<u64>>());
+    positions.sort();
+    assert_eq!(positions, (0..100).collect::<Vec<u64>>());
+}
+
+#[test]
+fn test_key_is_deterministic() {
+    let order = BitOrder::from_key("correct horse").unwrap();
+    assert_eq!(order, BitOrder::from_key("correct horse").unwrap());
+    assert_ne!(order, BitOrder::from_key("battery staple").unwrap());
+}
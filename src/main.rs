@@ -1,13 +1,38 @@
-// mod args;
-mod chunk;
-mod chunk_type;
-// mod commands;
-mod png;
-use anyhow::Result;
+mod args;
+mod commands;
 
-// pub type Error = Box<dyn std::error::Error>;
-// pub type Result<T> = std::result::Result<T, Error>;
+use std::process::ExitCode;
 
-fn main() -> Result<()> {
-    todo!()
+use args::{Cli, Command};
+use clap::Parser;
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let result = match cli.command {
+        Command::Encode(args) => commands::encode(args),
+        Command::Decode(args) => commands::decode(args),
+        Command::Remove(args) => commands::remove(args),
+        Command::Print(args) => commands::print(args),
+        Command::Check(args) => commands::check(args),
+        Command::Recover(args) => commands::recover(args),
+        Command::Text(args) => commands::text(args),
+        Command::Interlace(args) => commands::interlace(args),
+        Command::Passes(args) => commands::passes(args),
+        Command::Capacity(args) => commands::capacity(args),
+        Command::Optimize(args) => commands::optimize(args),
+        Command::Strip(args) => commands::strip(args),
+        Command::Frames(args) => commands::frames(args),
+        Command::Dump(args) => commands::dump(args),
+        Command::Build(args) => commands::build(args),
+        Command::Inspect(args) => commands::inspect(args),
+        Command::Diff(args) => commands::diff(args),
+        Command::Explain(args) => commands::explain(args),
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("error: {err:#}");
+            ExitCode::FAILURE
+        }
+    }
 }
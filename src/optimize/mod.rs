@@ -0,0 +1,185 @@
+//! Shrinking a png without changing its pixels. The image data is decoded
+//! and encoded again with every combination of filter strategy, zlib level
+//! and zlib strategy asked for, and the smallest result wins. The original
+//! image data is kept if nothing beats it.
+
+#[cfg(test)]
+mod unit_tests;
+
+use std::fmt::Display;
+
+use crate::{
+    chunk::Chunk,
+    chunk_type::ChunkType,
+    encoder::{self, DEFAULT_IDAT_LENGTH},
+    error::PngError,
+    filter::{FilterStrategy, FilterType},
+    image,
+    png::Png,
+    strip::{self, Policy, Removal},
+    zlib::{self, Strategy},
+};
+
+/// What to try while optimizing.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct OptimizeOptions {
+    pub filters: Vec<FilterStrategy>,
+    /// zlib levels, from 0 (store) to 9 (smallest)
+    pub levels: Vec<u32>,
+    pub strategies: Vec<Strategy>,
+    /// Metadata to strip at the same time, if any
+    pub strip: Option<Policy>,
+}
+
+impl Default for OptimizeOptions {
+    /// Every filter strategy and zlib strategy at the two highest levels
+    /// worth trying, keeping every chunk
+    fn default() -> Self {
+        OptimizeOptions {
+            filters: FilterType::ALL
+                .map(FilterStrategy::Fixed)
+                .into_iter()
+                .chain([FilterStrategy::Adaptive])
+                .collect(),
+            levels: vec![6, 9],
+            strategies: Strategy::ALL.to_vec(),
+            strip: None,
+        }
+    }
+}
+
+/// One way of encoding the image data.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Candidate {
+    pub filter: FilterStrategy,
+    pub level: u32,
+    pub strategy: Strategy,
+}
+
+impl Display for Candidate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} filter, zlib level {}, {} strategy",
+            self.filter, self.level, self.strategy
+        )
+    }
+}
+
+/// What optimizing changed.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Report {
+    /// Size of the file before, in bytes
+    pub old_length: u64,
+    /// Size of the file after, in bytes
+    pub new_length: u64,
+    /// The encoding that won, or `None` if the original image data was
+    /// already the smallest
+    pub winner: Option<Candidate>,
+    /// Chunks that were stripped, in file order
+    pub removed: Vec<Removal>,
+}
+
+impl Display for Report {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "old size: {} bytes", self.old_length)?;
+        let saved = self.old_length.saturating_sub(self.new_length);
+        writeln!(
+            f,
+            "new size: {} bytes, {:.1}% smaller",
+            self.new_length,
+            saved as f64 * 100.0 / self.old_length.max(1) as f64
+        )?;
+        match &self.winner {
+            Some(winner) => write!(f, "winner: {winner}")?,
+            None => write!(f, "winner: the original image data")?,
+        }
+        for removal in &self.removed {
+            write!(f, "\nremoved {removal}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Find the smallest encoding of the image data of a png. Returns the
+/// optimized png, which decodes to exactly the same scanlines, and what
+/// changed.
+pub fn optimize(png: &Png, options: &OptimizeOptions) -> Result<(Png, Report), PngError> {
+    if let Some(level) = options.levels.iter().find(|&&level| level > 9) {
+        return Err(PngError::InvalidImage {
+            reason: format!("zlib level {level} is out of range, levels go from 0 to 9"),
+            offset: 0,
+            index: 0,
+        });
+    }
+    let (header, raw) = image::scanlines(png)?;
+    let is_idat = |chunk: &Chunk| *chunk.chunk_type() == ChunkType::IDAT;
+    let old_data_length: u64 = png
+        .chunks()
+        .iter()
+        .filter(|chunk| is_idat(chunk))
+        .map(|chunk| u64::from(chunk.length()) + 12)
+        .sum();
+
+    let mut best: Option<(Candidate, Vec<Chunk>, u64)> = None;
+    for &filter in &options.filters {
+        let filtered = encoder::encode_scanlines(&raw, &header, filter);
+        for &level in &options.levels {
+            for &strategy in &options.strategies {
+                let data = zlib::deflate_with_strategy(&filtered, level, strategy);
+                let chunks = data.len().div_ceil(DEFAULT_IDAT_LENGTH).max(1) as u64;
+                let length = data.len() as u64 + 12 * chunks;
+                if best.as_ref().is_none_or(|(_, _, best)| length < *best) {
+                    let candidate = Candidate {
+                        filter,
+                        level,
+                        strategy,
+                    };
+                    let idats = data
+                        .chunks(DEFAULT_IDAT_LENGTH)
+                        .map(|data| Chunk::new(ChunkType::IDAT, data.to_vec()))
+                        .collect();
+                    best = Some((candidate, idats, length));
+                }
+            }
+        }
+    }
+    let (winner, mut idats) = match best {
+        Some((candidate, idats, length)) if length < old_data_length => (Some(candidate), idats),
+        _ => (None, Vec::new()),
+    };
+
+    let removed = match &options.strip {
+        Some(policy) => strip::removals(png, policy),
+        None => Vec::new(),
+    };
+    let mut chunks = Vec::new();
+    for (index, chunk) in png.chunks().iter().enumerate() {
+        if removed.iter().any(|removal| removal.index == index) {
+            continue;
+        }
+        if is_idat(chunk) && winner.is_some() {
+            // the new image data goes where the old started
+            chunks.append(&mut idats);
+        } else {
+            chunks.push(Chunk::new(*chunk.chunk_type(), chunk.data().to_vec()));
+        }
+    }
+    let mut optimized = Png::from_chunks(chunks);
+    optimized.set_trailing_bytes(png.trailing_bytes().to_vec());
+
+    if image::scanlines(&optimized)?.1 != raw {
+        return Err(PngError::InvalidImage {
+            reason: "optimized image data doesn't decode to the original pixels".to_string(),
+            offset: 0,
+            index: 0,
+        });
+    }
+    let report = Report {
+        old_length: png.byte_len(),
+        new_length: optimized.byte_len(),
+        winner,
+        removed,
+    };
+    Ok((optimized, report))
+}
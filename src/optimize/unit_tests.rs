@@ -0,0 +1,95 @@
+use super::*;
+use crate::{
+    encoder::{encode_with_options, EncodeOptions},
+    image_header::ColorType,
+    text::TextChunk,
+};
+
+/// A smooth gradient stored as badly as possible: unfiltered, uncompressed
+/// and split into many IDAT chunks
+fn bloated_png() -> Png {
+    let pixels: Vec<u8> = (0..32u32)
+        .flat_map(|y| (0..32u32).flat_map(move |x| [(x * 8) as u8, (y * 8) as u8, 128]))
+        .collect();
+    let options = EncodeOptions {
+        filter: FilterStrategy::Fixed(FilterType::None),
+        compression_level: 0,
+        idat_length: 256,
+        ..Default::default()
+    };
+    let mut png = encode_with_options(32, 32, ColorType::Rgb, 8, &pixels, &options).unwrap();
+    png.set_text(TextChunk::new("Comment", "made for testing"))
+        .unwrap();
+    png
+}
+
+#[test]
+fn test_optimize() {
+    let png = bloated_png();
+    let (optimized, report) = optimize(&png, &OptimizeOptions::default()).unwrap();
+    assert_eq!(report.old_length, png.byte_len());
+    assert_eq!(report.new_length, optimized.byte_len());
+    assert!(report.new_length < report.old_length / 2);
+    assert!(report.winner.is_some());
+    assert!(report.removed.is_empty());
+    assert_eq!(
+        image::scanlines(&optimized).unwrap(),
+        image::scanlines(&png).unwrap()
+    );
+    assert_eq!(optimized.to_string(), "IHDR\r\nIDAT\r\ntEXt\r\nIEND\r\n");
+}
+
+#[test]
+fn test_already_optimal() {
+    let (once, _) = optimize(&bloated_png(), &OptimizeOptions::default()).unwrap();
+    let (twice, report) = optimize(&once, &OptimizeOptions::default()).unwrap();
+    assert_eq!(report.winner, None);
+    assert_eq!(report.old_length, report.new_length);
+    assert_eq!(twice.as_bytes(), once.as_bytes());
+}
+
+#[test]
+fn test_strip() {
+    let options = OptimizeOptions {
+        strip: Some(Policy::all_ancillary()),
+        ..Default::default()
+    };
+    let (optimized, report) = optimize(&bloated_png(), &options).unwrap();
+    assert_eq!(report.removed.len(), 1);
+    assert_eq!(report.removed[0].keyword.as_deref(), Some("Comment"));
+    assert_eq!(optimized.to_string(), "IHDR\r\nIDAT\r\nIEND\r\n");
+
+    let options = OptimizeOptions {
+        strip: Some(Policy::keep_text(["Comment"])),
+        ..Default::default()
+    };
+    let (_, report) = optimize(&bloated_png(), &options).unwrap();
+    assert!(report.removed.is_empty());
+}
+
+#[test]
+fn test_level_out_of_range() {
+    let options = OptimizeOptions {
+        levels: vec![9, 12],
+        ..Default::default()
+    };
+    assert!(matches!(
+        optimize(&bloated_png(), &options),
+        Err(PngError::InvalidImage { .. })
+    ));
+}
+
+#[test]
+fn test_single_candidate() {
+    let options = OptimizeOptions {
+        filters: vec![FilterStrategy::Fixed(FilterType::Sub)],
+        levels: vec![9],
+        strategies: vec![Strategy::Rle],
+        strip: None,
+    };
+    let (_, report) = optimize(&bloated_png(), &options).unwrap();
+    assert_eq!(
+        report.winner.unwrap().to_string(),
+        "sub filter, zlib level 9, rle strategy"
+    );
+}
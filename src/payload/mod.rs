@@ -0,0 +1,204 @@
+//! Payloads split across several chunks of the same type.
+//!
+//! A payload that is too big for one chunk, or that would stand out as one
+//! large chunk, is cut into pieces. Each piece is stored in its own chunk
+//! with a small header in front of its share of the payload:
+//!
+//! | Field                     | Size    |
+//! |---------------------------|---------|
+//! | Magic, `\x89SEQ`          | 4 bytes |
+//! | Sequence number, from 0   | 4 bytes |
+//! | Total number of pieces    | 4 bytes |
+//! | CRC-32 of the whole payload | 4 bytes |
+//!
+//! The pieces can be stored in any order and are put back together by
+//! sequence number.
+//!
+//! Payloads can also be compressed before they are stored. A compressed
+//! payload starts with the magic `\x89ZLB` and the uncompressed length as an
+//! 8 byte integer, followed by the zlib stream.
+
+#[cfg(test)]
+mod unit_tests;
+
+use crc::{Crc, CRC_32_ISO_HDLC};
+
+use crate::{chunk::Chunk, chunk_type::ChunkType, error::PngError, zlib};
+
+/// Marks chunk data as a piece of a split payload
+pub const MAGIC: [u8; 4] = *b"\x89SEQ";
+/// Bytes of header in front of each piece
+pub const HEADER_LENGTH: usize = 16;
+/// Refuse to join payloads claiming more pieces than this
+pub const MAX_PIECES: u32 = 1 << 20;
+
+/// Marks a payload as zlib compressed
+pub const COMPRESSED_MAGIC: [u8; 4] = *b"\x89ZLB";
+/// Compressed payloads are not inflated past this many bytes unless the
+/// caller asks for a different limit
+pub const DEFAULT_MAX_INFLATED: u64 = 64 * 1024 * 1024;
+
+const CRC: Crc<u32> = Crc::<u32>::new(&CRC_32_ISO_HDLC);
+
+/// Whether chunk data starts like a piece of a split payload
+pub fn is_piece(data: &[u8]) -> bool {
+    data.len() >= HEADER_LENGTH && data.starts_with(&MAGIC)
+}
+
+/// Cut a payload into chunks of `chunk_type` holding at most `piece_length`
+/// bytes of it each
+pub fn split(
+    payload: &[u8],
+    chunk_type: ChunkType,
+    piece_length: usize,
+) -> Result<Vec<Chunk>, PngError> {
+    let max_piece = Chunk::MAX_LENGTH as usize - HEADER_LENGTH;
+    if piece_length == 0 || piece_length > max_piece {
+        return Err(invalid(format!(
+            "piece length {piece_length} must be between 1 and {max_piece}"
+        )));
+    }
+    let pieces: Vec<&[u8]> = match payload.is_empty() {
+        true => vec![&[]],
+        false => payload.chunks(piece_length).collect(),
+    };
+    let total = u32::try_from(pieces.len())
+        .ok()
+        .filter(|&total| total <= MAX_PIECES)
+        .ok_or_else(|| invalid(format!("{} pieces is too many", pieces.len())))?;
+    let checksum = CRC.checksum(payload);
+
+    Ok(pieces
+        .into_iter()
+        .zip(0u32..)
+        .map(|(piece, sequence)| {
+            let mut data = Vec::with_capacity(HEADER_LENGTH + piece.len());
+            data.extend(MAGIC);
+            data.extend(sequence.to_be_bytes());
+            data.extend(total.to_be_bytes());
+            data.extend(checksum.to_be_bytes());
+            data.extend(piece);
+            Chunk::new(chunk_type, data)
+        })
+        .collect())
+}
+
+/// Put a payload back together from its pieces, which may be in any order.
+/// Fails with [`PngError::IncompletePayload`] listing the missing and
+/// duplicated sequence numbers if the pieces don't add up.
+///
+/// Errors in one piece are reported at offset 0 and at the index of that
+/// piece among `chunks`, and errors in the payload as a whole at the first
+/// piece, for the caller to move to the position of that chunk in the file.
+pub fn join<'a>(chunks: impl IntoIterator<Item = &'a Chunk>) -> Result<Vec<u8>, PngError> {
+    let mut header: Option<(u32, u32)> = None;
+    let mut pieces: Vec<Option<&[u8]>> = Vec::new();
+    let mut duplicated = Vec::new();
+
+    for (index, chunk) in chunks.into_iter().enumerate() {
+        let data = chunk.data();
+        if !is_piece(data) {
+            return Err(invalid("not a piece of a split payload").at(0, index));
+        }
+        let word =
+            |at: usize| u32::from_be_bytes([data[at], data[at + 1], data[at + 2], data[at + 3]]);
+        let (sequence, total, checksum) = (word(4), word(8), word(12));
+        match header {
+            None if total == 0 || total > MAX_PIECES => {
+                return Err(invalid(format!("invalid piece count {total}")).at(0, index));
+            }
+            None => {
+                header = Some((total, checksum));
+                pieces.resize(total as usize, None);
+            }
+            Some(expected) if expected != (total, checksum) => {
+                return Err(invalid(format!(
+                    "piece {sequence} belongs to a different payload, {total} pieces with checksum {checksum} instead of {} with {}",
+                    expected.0, expected.1
+                ))
+                .at(0, index));
+            }
+            Some(_) => {}
+        }
+        match pieces.get_mut(sequence as usize) {
+            Some(Some(_)) => duplicated.push(sequence),
+            Some(slot) => *slot = Some(&data[HEADER_LENGTH..]),
+            None => {
+                return Err(invalid(format!(
+                    "sequence number {sequence} is past the last piece {}",
+                    total - 1
+                ))
+                .at(0, index))
+            }
+        }
+    }
+
+    let (_, checksum) = header.ok_or_else(|| invalid("no pieces to join"))?;
+    let missing: Vec<u32> = (0u32..)
+        .zip(&pieces)
+        .filter(|(_, piece)| piece.is_none())
+        .map(|(sequence, _)| sequence)
+        .collect();
+    if !missing.is_empty() || !duplicated.is_empty() {
+        return Err(PngError::IncompletePayload {
+            missing,
+            duplicated,
+            offset: 0,
+            index: 0,
+        });
+    }
+
+    let payload: Vec<u8> = pieces.into_iter().flatten().flatten().copied().collect();
+    if CRC.checksum(&payload) != checksum {
+        return Err(invalid(format!(
+            "checksum {} of the joined payload doesn't match {checksum}",
+            CRC.checksum(&payload)
+        )));
+    }
+    Ok(payload)
+}
+
+/// Whether a payload starts like one made by [`compress`]
+pub fn is_compressed(data: &[u8]) -> bool {
+    data.len() >= 12 && data.starts_with(&COMPRESSED_MAGIC)
+}
+
+/// zlib compress a payload and mark it as compressed
+pub fn compress(payload: &[u8]) -> Vec<u8> {
+    let mut data = COMPRESSED_MAGIC.to_vec();
+    data.extend((payload.len() as u64).to_be_bytes());
+    data.extend(zlib::deflate(payload, 9));
+    data
+}
+
+/// Undo [`compress`], refusing to inflate past `limit` bytes
+pub fn decompress(data: &[u8], limit: u64) -> Result<Vec<u8>, PngError> {
+    if !is_compressed(data) {
+        return Err(invalid("not a compressed payload"));
+    }
+    let length = u64::from_be_bytes(data[4..12].try_into().expect("8 bytes"));
+    if length > limit {
+        return Err(PngError::Decompression {
+            reason: format!("payload is {length} bytes, more than the limit of {limit}"),
+            offset: 0,
+            index: 0,
+        });
+    }
+    let payload = zlib::inflate(&data[12..], length)?;
+    if payload.len() as u64 != length {
+        return Err(PngError::Decompression {
+            reason: format!("payload is {} bytes, expected {length}", payload.len()),
+            offset: 0,
+            index: 0,
+        });
+    }
+    Ok(payload)
+}
+
+fn invalid(reason: impl Into<String>) -> PngError {
+    PngError::InvalidPayload {
+        reason: reason.into(),
+        offset: 0,
+        index: 0,
+    }
+}
@@ -0,0 +1,134 @@
+use super::*;
+use std::str::FromStr;
+
+fn testing_payload() -> Vec<u8> {
+    (0..1000u32).map(|i| (i * 7 % 251) as u8).collect()
+}
+
+fn testing_pieces() -> Vec<Chunk> {
+    split(
+        &testing_payload(),
+        ChunkType::from_str("ruSt").unwrap(),
+        300,
+    )
+    .unwrap()
+}
+
+fn sequence(chunk: &Chunk) -> u32 {
+    u32::from_be_bytes(chunk.data()[4..8].try_into().unwrap())
+}
+
+#[test]
+fn test_split() {
+    let pieces = testing_pieces();
+    assert_eq!(pieces.len(), 4);
+    assert!(pieces.iter().all(|chunk| is_piece(chunk.data())));
+    assert_eq!(pieces[3].data().len(), HEADER_LENGTH + 100);
+    assert_eq!(
+        pieces.iter().map(sequence).collect::<Vec<_>>(),
+        [0, 1, 2, 3]
+    );
+}
+
+#[test]
+fn test_join_in_any_order() {
+    let pieces = testing_pieces();
+    assert_eq!(join(&pieces).unwrap(), testing_payload());
+    assert_eq!(join(pieces.iter().rev()).unwrap(), testing_payload());
+}
+
+#[test]
+fn test_empty_payload() {
+    let pieces = split(&[], ChunkType::from_str("ruSt").unwrap(), 300).unwrap();
+    assert_eq!(pieces.len(), 1);
+    assert_eq!(join(&pieces).unwrap(), Vec::<u8>::new());
+}
+
+#[test]
+fn test_missing_and_duplicated() {
+    let pieces = testing_pieces();
+    let chosen = [&pieces[0], &pieces[2], &pieces[2], &pieces[0]];
+    match join(chosen) {
+        Err(PngError::IncompletePayload {
+            missing,
+            duplicated,
+            ..
+        }) => {
+            assert_eq!(missing, [1, 3]);
+            assert_eq!(duplicated, [2, 0]);
+        }
+        other => panic!("expected incomplete payload, got {other:?}"),
+    }
+}
+
+#[test]
+fn test_corrupt_piece() {
+    let mut pieces = testing_pieces();
+    let chunk_type = *pieces[1].chunk_type();
+    let mut data = pieces[1].data().to_vec();
+    data[HEADER_LENGTH] ^= 1;
+    pieces[1] = Chunk::new(chunk_type, data);
+    assert!(matches!(
+        join(&pieces),
+        Err(PngError::InvalidPayload { .. })
+    ));
+}
+
+#[test]
+fn test_mixed_payloads() {
+    let mut pieces = testing_pieces();
+    pieces.extend(split(b"another", ChunkType::from_str("ruSt").unwrap(), 300).unwrap());
+    assert!(matches!(
+        join(&pieces),
+        Err(PngError::InvalidPayload { index: 4, .. })
+    ));
+}
+
+#[test]
+fn test_not_pieces() {
+    let chunk = Chunk::new(
+        ChunkType::from_str("ruSt").unwrap(),
+        b"plain message".to_vec(),
+    );
+    assert!(!is_piece(chunk.data()));
+    assert!(join([&chunk]).is_err());
+    assert!(join([]).is_err());
+}
+
+#[test]
+fn test_invalid_piece_length() {
+    let chunk_type = ChunkType::from_str("ruSt").unwrap();
+    assert!(split(b"payload", chunk_type, 0).is_err());
+    assert!(split(b"payload", chunk_type, Chunk::MAX_LENGTH as usize).is_err());
+}
+
+#[test]
+fn test_compress_round_trip() {
+    let payload = b"compress me please, compress me please".repeat(50);
+    let compressed = compress(&payload);
+    assert!(is_compressed(&compressed));
+    assert!(compressed.len() < payload.len() / 10);
+    assert_eq!(
+        decompress(&compressed, DEFAULT_MAX_INFLATED).unwrap(),
+        payload
+    );
+    assert!(!is_compressed(&payload));
+}
+
+#[test]
+fn test_decompress_limit() {
+    let compressed = compress(&[0; 10_000]);
+    assert!(decompress(&compressed, 10_000).is_ok());
+    assert!(matches!(
+        decompress(&compressed, 9_999),
+        Err(PngError::Decompression { .. })
+    ));
+
+    // a header that understates the size mustn't let more through
+    let mut lying = compressed;
+    lying[4..12].copy_from_slice(&100u64.to_be_bytes());
+    assert!(matches!(
+        decompress(&lying, 10_000),
+        Err(PngError::Decompression { .. })
+    ));
+}
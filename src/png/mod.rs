@@ -1,13 +1,28 @@
 #[cfg(test)]
 mod unit_tests;
 
-use std::fmt::Display;
+use std::{fmt::Display, io::Read};
 
-use crate::chunk::Chunk;
-use anyhow::{anyhow, Error, Result};
+use crate::{
+    apng::Animation,
+    chunk::Chunk,
+    chunk_type::ChunkType,
+    encoder::{self, EncodeOptions},
+    error::PngError,
+    image::{self, Image},
+    image_header::{ImageHeader, InterlaceMethod},
+    reader::PngReader,
+    recover,
+    strip::{self, Policy, Removal},
+    text::TextChunk,
+    validate::{self, Violation},
+};
 
-struct Png {
+/// A PNG file: the standard signature followed by a list of chunks.
+pub struct Png {
     chunks: Vec<Chunk>,
+    /// Bytes after IEND, or that couldn't be parsed as chunks
+    trailing: Vec<u8>,
 }
 
 impl Display for Png {
@@ -22,33 +37,30 @@ impl Display for Png {
 }
 
 impl TryFrom<&[u8]> for Png {
-    type Error = Error;
+    type Error = PngError;
 
     fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
         // split byte array into chunks then add chunks to png struct
-        let signature: [u8; 8] = value[0..8].try_into()?;
-        if signature != Png::STANDARD_HEADER {
-            return Err(anyhow!("signature {:?} does not match expected", signature));
+        if !value.starts_with(&Png::STANDARD_HEADER) {
+            return Err(PngError::BadSignature {
+                found: value.iter().take(8).copied().collect(),
+                offset: 0,
+                index: 0,
+            });
         };
         let mut index = 8;
         let mut png = Png::from_chunks(Default::default());
-        loop {
-            println!("index: {} value len {}", index, value.len());
-            let chunk_vec: Vec<u8> = value[index..].try_into()?;
-            match Chunk::try_from(&chunk_vec) {
-                Ok(chunk) => {
-                    index += chunk.length() as usize + 12;
-                    if format!("{}", &chunk.chunk_type()) == "IEND" {
-                        png.append_chunk(chunk);
-                        break;
-                    }
-                    png.append_chunk(chunk);
-                }
-                Err(err) => {
-                    println!("Oops {}", err);
-                    break;
-                }
-            };
+        while index < value.len() {
+            let (chunk, length) = Chunk::parse_raw(&value[index..])
+                .and_then(|(chunk, length)| chunk.verify_crc().map(|_| (chunk, length)))
+                .map_err(|err| err.at(index as u64, png.chunks.len()))?;
+            index += length;
+            if *chunk.chunk_type() == ChunkType::IEND {
+                png.append_chunk(chunk);
+                png.trailing = value[index..].to_vec();
+                break;
+            }
+            png.append_chunk(chunk);
         }
         Ok(png)
     }
@@ -58,46 +70,313 @@ impl Png {
     pub const STANDARD_HEADER: [u8; 8] = [137, 80, 78, 71, 13, 10, 26, 10];
     /// Create a new png struct from a collection of chunks
     pub fn from_chunks(chunks: Vec<Chunk>) -> Png {
-        Png { chunks }
+        Png {
+            chunks,
+            trailing: Vec::new(),
+        }
+    }
+    /// Parse a png so that [`Png::as_bytes`] gives back exactly the same
+    /// bytes. Chunks keep the CRC they were stored with even when it is
+    /// wrong, and anything after IEND or from the first chunk that can't be
+    /// parsed onwards is kept as trailing bytes. Only the signature is
+    /// checked.
+    pub fn from_bytes_raw(value: &[u8]) -> Result<Png, PngError> {
+        if !value.starts_with(&Png::STANDARD_HEADER) {
+            return Err(PngError::BadSignature {
+                found: value.iter().take(8).copied().collect(),
+                offset: 0,
+                index: 0,
+            });
+        };
+        let mut index = 8;
+        let mut png = Png::from_chunks(Vec::new());
+        while let Ok((chunk, length)) = Chunk::parse_raw(&value[index..]) {
+            index += length;
+            let is_end = *chunk.chunk_type() == ChunkType::IEND;
+            png.append_chunk(chunk);
+            if is_end {
+                break;
+            }
+        }
+        png.trailing = value[index..].to_vec();
+        Ok(png)
+    }
+    /// Read a png from a stream, one chunk at a time, failing on the first
+    /// invalid chunk
+    pub fn from_reader<R: Read>(reader: R) -> Result<Png, PngError> {
+        let chunks = PngReader::new(reader)?.collect::<Result<Vec<Chunk>, PngError>>()?;
+        Ok(Png::from_chunks(chunks))
+    }
+    /// Parse a damaged png, skipping past anything that can't be read.
+    /// Returns the chunks that were salvaged and every problem found along
+    /// the way.
+    pub fn recover(value: &[u8]) -> (Png, Vec<PngError>) {
+        recover::recover(value)
     }
-    /// Add a chunk to this png
+    /// Add a chunk after every other chunk, even IEND. Use
+    /// [`Png::insert`] for a chunk that decoders should read.
     pub fn append_chunk(&mut self, chunk: Chunk) {
         self.chunks.push(chunk);
     }
-    /// Remove a chunk from this png by its stated name, if that chunk exists
-    pub fn remove_chunk(&mut self, chunk_type: &str) -> Result<Chunk> {
+    /// Insert a chunk where the spec allows chunks of its type, as worked
+    /// out by [`validate::default_position`]. Returns the index it was
+    /// inserted at.
+    pub fn insert(&mut self, chunk: Chunk) -> usize {
+        let index = validate::default_position(&self.chunks, chunk.chunk_type());
+        self.chunks.insert(index, chunk);
+        index
+    }
+    /// Insert a chunk at `index`, moving the chunks from there on back
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is more than the number of chunks.
+    pub fn insert_at(&mut self, index: usize, chunk: Chunk) {
+        self.chunks.insert(index, chunk);
+    }
+    /// Insert a chunk just before the first chunk of the given type.
+    /// Returns the index it was inserted at.
+    pub fn insert_before(
+        &mut self,
+        chunk_type: ChunkType,
+        chunk: Chunk,
+    ) -> Result<usize, PngError> {
+        let index = self
+            .chunks
+            .iter()
+            .position(|other| *other.chunk_type() == chunk_type)
+            .ok_or_else(|| self.not_found(chunk_type))?;
+        self.chunks.insert(index, chunk);
+        Ok(index)
+    }
+    /// Insert a chunk just after the last chunk of the given type, so that
+    /// a run of chunks like IDAT isn't split. Returns the index it was
+    /// inserted at.
+    pub fn insert_after(&mut self, chunk_type: ChunkType, chunk: Chunk) -> Result<usize, PngError> {
+        let index = self
+            .chunks
+            .iter()
+            .rposition(|other| *other.chunk_type() == chunk_type)
+            .ok_or_else(|| self.not_found(chunk_type))?
+            + 1;
+        self.chunks.insert(index, chunk);
+        Ok(index)
+    }
+    /// Remove a chunk from this png by its stated name, if that chunk exists.
+    /// Only the first chunk of that type is removed, see
+    /// [`Png::remove_all`] for the rest.
+    pub fn remove_chunk(&mut self, chunk_type: ChunkType) -> Result<Chunk, PngError> {
         match self
             .chunks
             .iter()
-            .position(|chunk| format!("{}", chunk.chunk_type()) == chunk_type)
+            .position(|chunk| *chunk.chunk_type() == chunk_type)
         {
             Some(index) => Ok(self.chunks.remove(index)),
-            None => Err(anyhow!("chunk {} not found", chunk_type)),
+            None => Err(self.not_found(chunk_type)),
         }
     }
-    /// Get the header chunk of this png
-    pub fn header(&self) -> &[u8; 8] {
-        // match self.chunk_by_type("IHDR") {
-        //     Some(chunk) => &chunk.as_bytes(),
-        //     None => [],
-        // }
-        todo!()
+    /// Remove every chunk of this type, returning them in file order
+    pub fn remove_all(&mut self, chunk_type: ChunkType) -> Vec<Chunk> {
+        self.chunks
+            .extract_if(.., |chunk| *chunk.chunk_type() == chunk_type)
+            .collect()
+    }
+    /// Remove the chunk at `index`, if there is one
+    pub fn remove_at(&mut self, index: usize) -> Option<Chunk> {
+        (index < self.chunks.len()).then(|| self.chunks.remove(index))
+    }
+    /// Put `chunk` in place of the first chunk of this type, returning the
+    /// chunk it replaced
+    pub fn replace(&mut self, chunk_type: ChunkType, chunk: Chunk) -> Result<Chunk, PngError> {
+        let index = self
+            .chunks
+            .iter()
+            .position(|other| *other.chunk_type() == chunk_type)
+            .ok_or_else(|| self.not_found(chunk_type))?;
+        Ok(std::mem::replace(&mut self.chunks[index], chunk))
+    }
+    /// Keep only the chunks for which `keep` returns true, in their order
+    pub fn retain(&mut self, keep: impl FnMut(&Chunk) -> bool) {
+        self.chunks.retain(keep);
+    }
+    /// Swap the chunks at two indexes
+    ///
+    /// # Panics
+    ///
+    /// Panics if either index is not less than the number of chunks.
+    pub fn swap(&mut self, a: usize, b: usize) {
+        self.chunks.swap(a, b);
+    }
+    /// Remove every ancillary chunk the policy doesn't keep, returning what
+    /// was removed
+    pub fn strip(&mut self, policy: &Policy) -> Vec<Removal> {
+        let removals = strip::removals(self, policy);
+        self.chunks.retain(|chunk| policy.keeps(chunk));
+        removals
+    }
+    /// Decode the IHDR chunk of this png into its image dimensions and format
+    pub fn header(&self) -> Result<ImageHeader, PngError> {
+        let index = self
+            .chunks
+            .iter()
+            .position(|chunk| *chunk.chunk_type() == ChunkType::IHDR)
+            .ok_or_else(|| self.not_found(ChunkType::IHDR))?;
+        ImageHeader::try_from(self.chunks[index].data())
+            .map_err(|err| err.at(self.chunk_offset(index), index))
+    }
+    /// Decode the pixels of this png to 8-bit RGBA
+    pub fn image(&self) -> Result<Image, PngError> {
+        Image::decode(self)
+    }
+    /// Read the APNG animation chunks of this png
+    pub fn animation(&self) -> Result<Animation, PngError> {
+        Animation::parse(self)
+    }
+    /// Re-encode the image data with or without Adam7 interlacing. The
+    /// pixels stay the same.
+    pub fn set_interlace_method(&mut self, method: InterlaceMethod) -> Result<(), PngError> {
+        let (mut header, raw) = image::scanlines(self)?;
+        header.interlace_method = method;
+        self.set_scanlines(&header, &raw)
+    }
+    /// Replace the image data with newly encoded scanlines, in the layout
+    /// [`image::scanlines`] gives them. IHDR is replaced by `header` and the
+    /// IDAT chunks by new ones where the first used to be.
+    pub fn set_scanlines(&mut self, header: &ImageHeader, raw: &[u8]) -> Result<(), PngError> {
+        let is_type = |chunk: &Chunk, chunk_type: ChunkType| *chunk.chunk_type() == chunk_type;
+        let ihdr = self
+            .chunks
+            .iter()
+            .position(|chunk| is_type(chunk, ChunkType::IHDR))
+            .ok_or_else(|| self.not_found(ChunkType::IHDR))?;
+        let first = self
+            .chunks
+            .iter()
+            .position(|chunk| is_type(chunk, ChunkType::IDAT))
+            .ok_or_else(|| self.not_found(ChunkType::IDAT))?;
+        let data = encoder::image_data_chunks(raw, header, &EncodeOptions::default());
+        self.chunks[ihdr] = header.to_chunk();
+        self.chunks.retain(|chunk| !is_type(chunk, ChunkType::IDAT));
+        self.chunks.splice(first..first, data);
+        Ok(())
+    }
+    /// Check the chunks against the ordering and multiplicity rules of the
+    /// spec, returning every rule that is broken
+    pub fn validate(&self) -> Vec<Violation> {
+        validate::validate(&self.chunks)
+    }
+    /// Decode every tEXt, zTXt and iTXt chunk, in file order
+    pub fn text_chunks(&self) -> Result<Vec<TextChunk>, PngError> {
+        self.chunks
+            .iter()
+            .enumerate()
+            .filter(|(_, chunk)| TextChunk::is_text_chunk(chunk))
+            .map(|(index, chunk)| {
+                TextChunk::try_from(chunk).map_err(|err| err.at(self.chunk_offset(index), index))
+            })
+            .collect()
+    }
+    /// Decode the first text chunk with this keyword
+    pub fn text(&self, keyword: &str) -> Result<Option<TextChunk>, PngError> {
+        self.chunks
+            .iter()
+            .position(|chunk| TextChunk::keyword_of(chunk).as_deref() == Some(keyword))
+            .map(|index| {
+                TextChunk::try_from(&self.chunks[index])
+                    .map_err(|err| err.at(self.chunk_offset(index), index))
+            })
+            .transpose()
+    }
+    /// Store text under its keyword. The first text chunk with the same
+    /// keyword is replaced and any others removed; if there are none the
+    /// text is inserted with [`Png::insert`], just before IEND.
+    pub fn set_text(&mut self, text: TextChunk) -> Result<(), PngError> {
+        let chunk = text.to_chunk()?;
+        let has_keyword =
+            |chunk: &Chunk| TextChunk::keyword_of(chunk).as_deref() == Some(&text.keyword);
+        match self.chunks.iter().position(has_keyword) {
+            Some(first) => {
+                self.chunks[first] = chunk;
+                let mut index = 0;
+                self.chunks.retain(|chunk| {
+                    let keep = index <= first || !has_keyword(chunk);
+                    index += 1;
+                    keep
+                });
+            }
+            None => {
+                self.insert(chunk);
+            }
+        }
+        Ok(())
+    }
+    /// Remove every text chunk with this keyword, returning how many there
+    /// were
+    pub fn remove_text(&mut self, keyword: &str) -> usize {
+        let before = self.chunks.len();
+        self.chunks
+            .retain(|chunk| TextChunk::keyword_of(chunk).as_deref() != Some(keyword));
+        before - self.chunks.len()
     }
     pub fn chunks(&self) -> &[Chunk] {
         &self.chunks
     }
-    pub fn chunk_by_type(&self, chunk_type: &str) -> Option<&Chunk> {
+    /// The first chunk with this stated name, see [`Png::chunks_by_type`]
+    /// for all of them
+    pub fn chunk_by_type(&self, chunk_type: ChunkType) -> Option<&Chunk> {
         self.chunks
             .iter()
-            .find(|&chunk| format!("{}", chunk.chunk_type()) == chunk_type)
+            .find(|&chunk| *chunk.chunk_type() == chunk_type)
     }
-    fn as_bytes(&self) -> Vec<u8> {
-        // let mut bytes: Vec<u8> = ;
+    /// Every chunk of this type, in file order
+    pub fn chunks_by_type(&self, chunk_type: ChunkType) -> impl Iterator<Item = &Chunk> {
         self.chunks
+            .iter()
+            .filter(move |chunk| *chunk.chunk_type() == chunk_type)
+    }
+    /// The chunks of this png, for editing in place
+    pub fn chunks_mut(&mut self) -> &mut [Chunk] {
+        &mut self.chunks
+    }
+    /// The error for a chunk type this png doesn't have
+    fn not_found(&self, chunk_type: ChunkType) -> PngError {
+        PngError::ChunkNotFound {
+            chunk_type: chunk_type.to_string(),
+            offset: self.byte_len(),
+            index: self.chunks.len(),
+        }
+    }
+    /// Byte offset of the chunk at `index` once serialized
+    pub fn chunk_offset(&self, index: usize) -> u64 {
+        self.chunks[..index]
+            .iter()
+            .fold(Png::STANDARD_HEADER.len() as u64, |len, chunk| {
+                len + u64::from(chunk.length()) + 12
+            })
+    }
+    /// Bytes after IEND, or that couldn't be parsed as chunks
+    pub fn trailing_bytes(&self) -> &[u8] {
+        &self.trailing
+    }
+    /// Replace the bytes written after the last chunk
+    pub fn set_trailing_bytes(&mut self, trailing: Vec<u8>) {
+        self.trailing = trailing;
+    }
+    /// Number of bytes this png takes up once serialized
+    pub fn byte_len(&self) -> u64 {
+        self.chunk_offset(self.chunks.len()) + self.trailing.len() as u64
+    }
+    /// Serialize the signature, every chunk and any trailing bytes into a
+    /// single byte vector
+    pub fn as_bytes(&self) -> Vec<u8> {
+        let mut bytes = self
+            .chunks
             .iter()
             .fold(Png::STANDARD_HEADER.to_vec(), |mut b, chunk| {
                 b.append(&mut chunk.as_bytes());
                 b
-            })
+            });
+        bytes.extend_from_slice(&self.trailing);
+        bytes
     }
 }
@@ -6,13 +6,11 @@ use std::convert::TryFrom;
 use std::str::FromStr;
 
 fn testing_chunks() -> Vec<Chunk> {
-    let mut chunks = Vec::new();
-
-    chunks.push(chunk_from_strings("FrSt", "I am the first chunk").unwrap());
-    chunks.push(chunk_from_strings("miDl", "I am another chunk").unwrap());
-    chunks.push(chunk_from_strings("LASt", "I am the last chunk").unwrap());
-
-    chunks
+    vec![
+        chunk_from_strings("FrSt", "I am the first chunk").unwrap(),
+        chunk_from_strings("miDl", "I am another chunk").unwrap(),
+        chunk_from_strings("LASt", "I am the last chunk").unwrap(),
+    ]
 }
 
 fn testing_png() -> Png {
@@ -103,7 +101,9 @@ fn test_list_chunks() {
 #[test]
 fn test_chunk_by_type() {
     let png = testing_png();
-    let chunk = png.chunk_by_type("FrSt").unwrap();
+    let chunk = png
+        .chunk_by_type(ChunkType::from_str("FrSt").unwrap())
+        .unwrap();
     assert_eq!(&chunk.chunk_type().to_string(), "FrSt");
     assert_eq!(&chunk.data_as_string().unwrap(), "I am the first chunk");
 }
@@ -112,7 +112,9 @@ fn test_chunk_by_type() {
 fn test_append_chunk() {
     let mut png = testing_png();
     png.append_chunk(chunk_from_strings("TeSt", "Message").unwrap());
-    let chunk = png.chunk_by_type("TeSt").unwrap();
+    let chunk = png
+        .chunk_by_type(ChunkType::from_str("TeSt").unwrap())
+        .unwrap();
     assert_eq!(&chunk.chunk_type().to_string(), "TeSt");
     assert_eq!(&chunk.data_as_string().unwrap(), "Message");
 }
@@ -121,8 +123,9 @@ fn test_append_chunk() {
 fn test_remove_chunk() {
     let mut png = testing_png();
     png.append_chunk(chunk_from_strings("TeSt", "Message").unwrap());
-    png.remove_chunk("TeSt").unwrap();
-    let chunk = png.chunk_by_type("TeSt");
+    png.remove_chunk(ChunkType::from_str("TeSt").unwrap())
+        .unwrap();
+    let chunk = png.chunk_by_type(ChunkType::from_str("TeSt").unwrap());
     assert!(chunk.is_none());
 }
 
@@ -136,7 +139,7 @@ fn test_png_from_image_file() {
 fn test_as_bytes() {
     let png = Png::try_from(&PNG_FILE[..]).unwrap();
     let actual = png.as_bytes();
-    let expected: Vec<u8> = PNG_FILE.iter().copied().collect();
+    let expected: Vec<u8> = PNG_FILE.to_vec();
     assert_eq!(actual, expected);
 }
 
@@ -158,6 +161,303 @@ fn test_png_trait_impls() {
     let _png_string = format!("{}", png);
 }
 
+#[test]
+fn test_bad_signature_error() {
+    let err = Png::try_from(&PNG_FILE[1..]).err().unwrap();
+    assert!(matches!(err, PngError::BadSignature { offset: 0, .. }));
+
+    let err = Png::try_from(&PNG_FILE[..3]).err().unwrap();
+    assert!(matches!(err, PngError::BadSignature { found, .. } if found == PNG_FILE[..3]));
+}
+
+#[test]
+fn test_remove_missing_chunk_error() {
+    let mut png = testing_png();
+    let err = png
+        .remove_chunk(ChunkType::from_str("TeSt").unwrap())
+        .err()
+        .unwrap();
+    assert!(matches!(err, PngError::ChunkNotFound { index: 3, .. }));
+    assert_eq!(err.offset(), png.as_bytes().len() as u64);
+}
+
+#[test]
+fn test_header() {
+    let png = Png::try_from(&PNG_FILE[..]).unwrap();
+    let header = png.header().unwrap();
+    assert_eq!((header.width, header.height), (50, 50));
+    assert_eq!(header.bit_depth, 8);
+    assert_eq!(header.color_type, crate::image_header::ColorType::Rgba);
+}
+
+#[test]
+fn test_image() {
+    let png = Png::try_from(&PNG_FILE[..]).unwrap();
+    let image = png.image().unwrap();
+    assert_eq!((image.width, image.height), (50, 50));
+    assert_eq!(image.pixels().len(), 50 * 50 * 4);
+}
+
+#[test]
+fn test_set_interlace_method() {
+    let mut png = Png::try_from(&PNG_FILE[..]).unwrap();
+    let image = png.image().unwrap();
+    png.set_interlace_method(InterlaceMethod::Adam7).unwrap();
+    assert_eq!(
+        png.header().unwrap().interlace_method,
+        InterlaceMethod::Adam7
+    );
+    let reread = Png::try_from(png.as_bytes().as_slice()).unwrap();
+    assert_eq!(reread.image().unwrap(), image);
+    assert_eq!(reread.to_string(), png.to_string());
+
+    png.set_interlace_method(InterlaceMethod::None).unwrap();
+    assert_eq!(
+        png.header().unwrap().interlace_method,
+        InterlaceMethod::None
+    );
+    assert_eq!(png.image().unwrap(), image);
+}
+
+#[test]
+fn test_missing_header() {
+    let png = testing_png();
+    assert!(matches!(png.header(), Err(PngError::ChunkNotFound { .. })));
+}
+
+#[test]
+fn test_trailing_bytes_kept() {
+    let mut bytes = PNG_FILE.to_vec();
+    bytes.extend_from_slice(b"data after the end");
+    let png = Png::try_from(bytes.as_slice()).unwrap();
+    assert_eq!(png.trailing_bytes(), b"data after the end");
+    assert_eq!(png.as_bytes(), bytes);
+}
+
+#[test]
+fn test_raw_round_trip() {
+    let mut bytes = PNG_FILE.to_vec();
+    // corrupt the crc of the IHDR chunk
+    bytes[8 + 8 + 13] ^= 0xff;
+    bytes.extend_from_slice(b"data after the end");
+    let png = Png::from_bytes_raw(&bytes).unwrap();
+    assert!(png.chunks()[0].verify_crc().is_err());
+    assert_eq!(png.as_bytes(), bytes);
+    assert_eq!(png.byte_len(), bytes.len() as u64);
+}
+
+#[test]
+fn test_raw_unparsable_tail() {
+    let mut bytes = PNG_FILE[..8 + 25 + 7].to_vec();
+    bytes.extend_from_slice(&[0, 0, 0]);
+    let png = Png::from_bytes_raw(&bytes).unwrap();
+    assert_eq!(png.chunks().len(), 1);
+    assert_eq!(png.trailing_bytes(), &bytes[8 + 25..]);
+    assert_eq!(png.as_bytes(), bytes);
+}
+
+#[test]
+fn test_raw_edit_recalculates_crc() {
+    let mut bytes = PNG_FILE.to_vec();
+    bytes[8 + 8 + 13] ^= 0xff;
+    let mut png = Png::from_bytes_raw(&bytes).unwrap();
+    let header = png.header().unwrap().to_chunk();
+    png.remove_chunk(ChunkType::IHDR).unwrap();
+    png.chunks.insert(0, header);
+    assert_eq!(png.as_bytes(), PNG_FILE);
+}
+
+#[test]
+fn test_corrupt_chunk_is_error() {
+    let mut bytes = PNG_FILE.to_vec();
+    // corrupt the crc of the sRGB chunk
+    bytes[8 + 25 + 12] ^= 0xff;
+    let err = Png::try_from(bytes.as_slice()).err().unwrap();
+    assert!(matches!(err, PngError::CrcMismatch { index: 1, .. }));
+    assert_eq!(err.offset(), 8 + 25);
+
+    let err = Png::try_from(&PNG_FILE[..100]).err().unwrap();
+    assert!(matches!(err, PngError::TruncatedChunk { .. }));
+}
+
+#[test]
+fn test_recover() {
+    let mut bytes = PNG_FILE.to_vec();
+    bytes[8 + 25 + 12] ^= 0xff;
+    let (png, diagnostics) = Png::recover(&bytes);
+    assert_eq!(png.chunks().len(), 7);
+    assert_eq!(diagnostics.len(), 1);
+}
+
+#[test]
+fn test_set_and_remove_text() {
+    use crate::text::TextChunk;
+
+    let mut png = Png::try_from(&PNG_FILE[..]).unwrap();
+    png.set_text(TextChunk::new("Title", "Dice")).unwrap();
+    png.set_text(TextChunk::new("Author", "Wikipedia")).unwrap();
+    assert_eq!(
+        png.chunks()[png.chunks().len() - 1]
+            .chunk_type()
+            .to_string(),
+        "IEND"
+    );
+    assert_eq!(png.text("Title").unwrap().unwrap().text, "Dice");
+
+    png.append_chunk(TextChunk::new("Title", "Duplicate").to_chunk().unwrap());
+    png.set_text(TextChunk::new("Title", "Two dice")).unwrap();
+    let texts = png.text_chunks().unwrap();
+    assert_eq!(texts.len(), 2);
+    assert_eq!(texts[0], TextChunk::new("Title", "Two dice"));
+
+    assert_eq!(png.remove_text("Title"), 1);
+    assert_eq!(png.remove_text("Title"), 0);
+    assert!(png.text("Title").unwrap().is_none());
+}
+
+#[test]
+fn test_insert() {
+    let mut png = Png::try_from(&PNG_FILE[..]).unwrap();
+    let index = png.insert(chunk_from_strings("ruSt", "Message").unwrap());
+    assert_eq!(index, 6);
+    assert_eq!(
+        png.insert(chunk_from_strings("tIME", "1234567").unwrap()),
+        7
+    );
+    assert_eq!(png.insert(chunk_from_strings("bKGD", "123456").unwrap()), 4);
+    assert_eq!(
+        png.to_string(),
+        "IHDR\r\nsRGB\r\ngAMA\r\npHYs\r\nbKGD\r\nIDAT\r\nRuSt\r\nruSt\r\ntIME\r\nIEND\r\n"
+    );
+    assert!(png.validate().is_empty());
+}
+
+#[test]
+fn test_insert_before_and_after() {
+    let mut png = testing_png();
+    let middle = ChunkType::from_str("miDl").unwrap();
+    let before = png
+        .insert_before(middle, chunk_from_strings("BeFr", "").unwrap())
+        .unwrap();
+    let after = png
+        .insert_after(middle, chunk_from_strings("AfTr", "").unwrap())
+        .unwrap();
+    png.insert_at(0, chunk_from_strings("ZeRo", "").unwrap());
+    assert_eq!((before, after), (1, 3));
+    assert_eq!(
+        png.to_string(),
+        "ZeRo\r\nFrSt\r\nBeFr\r\nmiDl\r\nAfTr\r\nLASt\r\n"
+    );
+}
+
+#[test]
+fn test_insert_after_run() {
+    let mut png = Png::try_from(&PNG_FILE[..]).unwrap();
+    png.insert_at(5, chunk_from_strings("IDAT", "").unwrap());
+    let index = png
+        .insert_after(
+            ChunkType::from_str("IDAT").unwrap(),
+            chunk_from_strings("ruSt", "").unwrap(),
+        )
+        .unwrap();
+    assert_eq!(index, 6);
+}
+
+#[test]
+fn test_insert_before_missing_chunk() {
+    let mut png = testing_png();
+    let err = png
+        .insert_before(
+            ChunkType::from_str("IEND").unwrap(),
+            chunk_from_strings("ruSt", "").unwrap(),
+        )
+        .err()
+        .unwrap();
+    assert!(matches!(err, PngError::ChunkNotFound { index: 3, .. }));
+    assert_eq!(png.chunks().len(), 3);
+}
+
+/// The testing chunks with two more text chunks around the middle one
+fn texts_png() -> Png {
+    let mut png = testing_png();
+    png.insert_at(1, chunk_from_strings("tEXt", "Title\0Dice").unwrap());
+    png.insert_at(3, chunk_from_strings("tEXt", "Author\0Someone").unwrap());
+    png
+}
+
+fn text_type() -> ChunkType {
+    ChunkType::from_str("tEXt").unwrap()
+}
+
+#[test]
+fn test_chunks_by_type() {
+    let png = texts_png();
+    let texts: Vec<String> = png
+        .chunks_by_type(text_type())
+        .map(|chunk| chunk.data_as_string().unwrap())
+        .collect();
+    assert_eq!(texts, ["Title\0Dice", "Author\0Someone"]);
+    let missing = ChunkType::from_str("zTXt").unwrap();
+    assert_eq!(png.chunks_by_type(missing).count(), 0);
+}
+
+#[test]
+fn test_remove_all() {
+    let mut png = texts_png();
+    let removed = png.remove_all(text_type());
+    assert_eq!(removed.len(), 2);
+    assert_eq!(removed[1].data_as_string().unwrap(), "Author\0Someone");
+    assert_eq!(png.to_string(), "FrSt\r\nmiDl\r\nLASt\r\n");
+    assert!(png.remove_all(text_type()).is_empty());
+}
+
+#[test]
+fn test_remove_at() {
+    let mut png = texts_png();
+    let removed = png.remove_at(3).unwrap();
+    assert_eq!(removed.data_as_string().unwrap(), "Author\0Someone");
+    assert!(png.remove_at(4).is_none());
+    assert_eq!(png.chunks().len(), 4);
+}
+
+#[test]
+fn test_replace() {
+    let mut png = texts_png();
+    let old = png
+        .replace(
+            text_type(),
+            chunk_from_strings("tEXt", "Title\0Two dice").unwrap(),
+        )
+        .unwrap();
+    assert_eq!(old.data_as_string().unwrap(), "Title\0Dice");
+    assert_eq!(png.chunks()[1].data_as_string().unwrap(), "Title\0Two dice");
+
+    let missing = ChunkType::from_str("zTXt").unwrap();
+    let err = png
+        .replace(missing, chunk_from_strings("zTXt", "").unwrap())
+        .err()
+        .unwrap();
+    assert!(matches!(err, PngError::ChunkNotFound { index: 5, .. }));
+}
+
+#[test]
+fn test_retain_and_swap() {
+    let mut png = texts_png();
+    png.retain(|chunk| chunk.chunk_type().is_public());
+    assert_eq!(png.to_string(), "tEXt\r\ntEXt\r\nLASt\r\n");
+    png.swap(0, 2);
+    assert_eq!(png.to_string(), "LASt\r\ntEXt\r\ntEXt\r\n");
+}
+
+#[test]
+fn test_chunks_mut() {
+    let mut png = texts_png();
+    png.chunks_mut()[2] = chunk_from_strings("miDl", "Changed").unwrap();
+    png.chunks_mut().reverse();
+    assert_eq!(png.chunks()[2].data_as_string().unwrap(), "Changed");
+    assert_eq!(png.chunks()[0].chunk_type().to_string(), "LASt");
+}
+
 // This is the raw bytes for a shrunken version of the `dice.png` image on Wikipedia
 const PNG_FILE: [u8; 4803] = [
     137, 80, 78, 71, 13, 10, 26, 10, 0, 0, 0, 13, 73, 72, 68, 82, 0, 0, 0, 50, 0, 0, 0, 50, 8, 6,
@@ -0,0 +1,153 @@
+//! Streaming access to the chunks of a PNG file.
+//!
+//! [`Png::try_from`](crate::png::Png) needs the whole file in memory. A
+//! [`PngReader`] instead pulls one chunk at a time from any [`Read`]
+//! implementation, so large files and pipes can be scanned without buffering
+//! more than the chunk currently being looked at.
+
+#[cfg(test)]
+mod unit_tests;
+
+use std::io::{self, Read};
+
+use crate::{chunk::Chunk, chunk_type::ChunkType, error::PngError, png::Png};
+
+/// Reads a PNG signature followed by its chunks from an underlying reader.
+///
+/// The reader is an [`Iterator`] over the chunks of the file. Each chunk's
+/// CRC is checked as it is read, and iteration ends after the IEND chunk or
+/// when the input runs out cleanly on a chunk boundary. Once an error has
+/// been returned no further chunks are yielded.
+pub struct PngReader<R: Read> {
+    inner: R,
+    offset: u64,
+    index: usize,
+    done: bool,
+}
+
+impl<R: Read> PngReader<R> {
+    /// Read and check the PNG signature, leaving the reader positioned at the
+    /// first chunk
+    pub fn new(mut inner: R) -> Result<Self, PngError> {
+        let mut signature = [0; 8];
+        let found = read_up_to(&mut inner, &mut signature).map_err(|source| PngError::Io {
+            source,
+            offset: 0,
+            index: 0,
+        })?;
+        if signature[..found] != Png::STANDARD_HEADER {
+            return Err(PngError::BadSignature {
+                found: signature[..found].to_vec(),
+                offset: 0,
+                index: 0,
+            });
+        }
+        Ok(PngReader {
+            inner,
+            offset: Png::STANDARD_HEADER.len() as u64,
+            index: 0,
+            done: false,
+        })
+    }
+    /// Give back the underlying reader, positioned after the last chunk read
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+    /// Byte offset of the next chunk to be read
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+    /// Read the next chunk, or `None` if the input ended before a new chunk
+    fn read_chunk(&mut self) -> Result<Option<Chunk>, PngError> {
+        let (offset, index) = (self.offset, self.index);
+        let io_error = |source| PngError::Io {
+            source,
+            offset,
+            index,
+        };
+        let truncated = |needed: u64, available: u64| PngError::TruncatedChunk {
+            needed,
+            available,
+            offset,
+            index,
+        };
+
+        let mut start = [0; 8];
+        match read_up_to(&mut self.inner, &mut start).map_err(io_error)? {
+            0 => return Ok(None),
+            8 => {}
+            available => return Err(truncated(12, available as u64)),
+        }
+        let length = u32::from_be_bytes([start[0], start[1], start[2], start[3]]);
+        if length > Chunk::MAX_LENGTH {
+            return Err(PngError::LengthOverflow {
+                length: length.into(),
+                offset,
+                index,
+            });
+        }
+        let chunk_type = ChunkType::try_from([start[4], start[5], start[6], start[7]])
+            .map_err(|err| err.at(offset, index))?;
+
+        // don't trust the length field with an up front allocation
+        let mut data = Vec::new();
+        (&mut self.inner)
+            .take(length.into())
+            .read_to_end(&mut data)
+            .map_err(io_error)?;
+        let mut crc_bytes = [0; 4];
+        let crc_found = read_up_to(&mut self.inner, &mut crc_bytes).map_err(io_error)?;
+        if data.len() != length as usize || crc_found != 4 {
+            return Err(truncated(
+                u64::from(length) + 12,
+                (8 + data.len() + crc_found) as u64,
+            ));
+        }
+
+        let chunk = Chunk::new(chunk_type, data);
+        let crc = u32::from_be_bytes(crc_bytes);
+        if crc != chunk.crc() {
+            return Err(PngError::CrcMismatch {
+                chunk_type: chunk.chunk_type().to_string(),
+                expected: chunk.crc(),
+                actual: crc,
+                offset,
+                index,
+            });
+        }
+        self.offset += u64::from(length) + 12;
+        self.index += 1;
+        Ok(Some(chunk))
+    }
+}
+
+impl<R: Read> Iterator for PngReader<R> {
+    type Item = Result<Chunk, PngError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let next = self.read_chunk().transpose();
+        self.done = match &next {
+            Some(Ok(chunk)) => *chunk.chunk_type() == ChunkType::IEND,
+            _ => true,
+        };
+        next
+    }
+}
+
+/// Like [`Read::read_exact`], but stops quietly at end of file and returns
+/// how many bytes were read
+fn read_up_to<R: Read>(reader: &mut R, buf: &mut [u8]) -> io::Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..]) {
+            Ok(0) => break,
+            Ok(n) => filled += n,
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+            Err(err) => return Err(err),
+        }
+    }
+    Ok(filled)
+}
@@ -0,0 +1,86 @@
+use super::*;
+use crate::test_utils;
+
+fn testing_bytes() -> Vec<u8> {
+    Png::from_chunks(vec![
+        test_utils::chunk("FrSt", b"I am the first chunk"),
+        test_utils::chunk("miDl", b"I am another chunk"),
+        test_utils::chunk("IEND", b""),
+    ])
+    .as_bytes()
+}
+
+#[test]
+fn test_reads_all_chunks() {
+    let bytes = testing_bytes();
+    let reader = PngReader::new(bytes.as_slice()).unwrap();
+    let types: Vec<String> = reader
+        .map(|chunk| chunk.unwrap().chunk_type().to_string())
+        .collect();
+    assert_eq!(types, ["FrSt", "miDl", "IEND"]);
+}
+
+#[test]
+fn test_stops_after_iend() {
+    let mut bytes = testing_bytes();
+    bytes.extend_from_slice(&test_utils::chunk("LASt", b"after the end").as_bytes());
+    let mut reader = PngReader::new(bytes.as_slice()).unwrap();
+    assert_eq!(reader.by_ref().count(), 3);
+    assert!(!reader.into_inner().is_empty(), "trailing bytes untouched");
+}
+
+#[test]
+fn test_ends_cleanly_without_iend() {
+    let bytes = Png::from_chunks(vec![test_utils::chunk("FrSt", b"only")]).as_bytes();
+    let chunks: Vec<_> = PngReader::new(bytes.as_slice()).unwrap().collect();
+    assert_eq!(chunks.len(), 1);
+    assert!(chunks[0].is_ok());
+}
+
+#[test]
+fn test_invalid_signature() {
+    let mut bytes = testing_bytes();
+    bytes[0] = 13;
+    assert!(PngReader::new(bytes.as_slice()).is_err());
+    assert!(PngReader::new(&bytes[..4]).is_err());
+}
+
+#[test]
+fn test_crc_mismatch() {
+    let mut bytes = testing_bytes();
+    // last byte of the first chunk's crc
+    bytes[8 + 12 + 20 - 1] ^= 0xff;
+    let mut reader = PngReader::new(bytes.as_slice()).unwrap();
+    assert!(reader.next().unwrap().is_err());
+    assert!(reader.next().is_none(), "no chunks after an error");
+}
+
+#[test]
+fn test_truncated_chunk() {
+    let bytes = testing_bytes();
+    for end in [8 + 3, 8 + 10, 8 + 12 + 18] {
+        let mut reader = PngReader::new(&bytes[..end]).unwrap();
+        assert!(reader.next().unwrap().is_err(), "truncated at {end}");
+    }
+}
+
+#[test]
+fn test_png_from_reader() {
+    let bytes = testing_bytes();
+    let png = Png::from_reader(bytes.as_slice()).unwrap();
+    assert_eq!(png.chunks().len(), 3);
+    assert_eq!(png.as_bytes(), bytes);
+}
+
+#[test]
+fn test_error_location() {
+    let mut bytes = testing_bytes();
+    // last byte of the second chunk's crc
+    bytes[8 + 32 + 30 - 1] ^= 0xff;
+    let mut reader = PngReader::new(bytes.as_slice()).unwrap();
+    assert!(reader.next().unwrap().is_ok());
+    let err = reader.next().unwrap().err().unwrap();
+    assert!(matches!(err, PngError::CrcMismatch { .. }));
+    assert_eq!(err.offset(), 40);
+    assert_eq!(err.index(), 1);
+}
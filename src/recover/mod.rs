@@ -0,0 +1,101 @@
+//! Lenient parsing of damaged PNG files.
+//!
+//! Instead of stopping at the first problem, [`recover`] records it and
+//! scans forward for the next plausible chunk, a chunk header followed by
+//! data whose CRC checks out, and carries on from there. Every problem found
+//! along the way is reported with its position in the file.
+
+#[cfg(test)]
+mod unit_tests;
+
+use crc::{Crc, CRC_32_ISO_HDLC};
+
+use crate::{chunk::Chunk, chunk_type::ChunkType, error::PngError, png::Png};
+
+/// The CRC used by chunks, over the chunk type and data
+const CHECKSUM: Crc<u32> = Crc::<u32>::new(&CRC_32_ISO_HDLC);
+
+/// Parse as much of `bytes` as possible, returning the chunks that could be
+/// salvaged and every problem found, in file order.
+///
+/// Chunks whose CRC doesn't match are kept, with a recalculated CRC, as long
+/// as the bytes after them look like the next chunk; otherwise their length
+/// is assumed to be corrupt and they are skipped. Bytes after IEND are kept as
+/// trailing bytes, but unreadable bytes at the end are dropped.
+pub fn recover(bytes: &[u8]) -> (Png, Vec<PngError>) {
+    let mut diagnostics = Vec::new();
+    if !bytes.starts_with(&Png::STANDARD_HEADER) {
+        diagnostics.push(PngError::BadSignature {
+            found: bytes.iter().take(8).copied().collect(),
+            offset: 0,
+            index: 0,
+        });
+    }
+    let mut chunks: Vec<Chunk> = Vec::new();
+    let mut position = Png::STANDARD_HEADER.len().min(bytes.len());
+    let mut trailing = Vec::new();
+
+    while position < bytes.len() {
+        let (offset, index) = (position as u64, chunks.len());
+        let error = match Chunk::parse_raw(&bytes[position..]) {
+            Ok((mut chunk, length)) => match chunk.verify_crc() {
+                Ok(()) => {
+                    position += length;
+                    let is_end = *chunk.chunk_type() == ChunkType::IEND;
+                    chunks.push(chunk);
+                    if is_end {
+                        trailing = bytes[position..].to_vec();
+                        break;
+                    }
+                    continue;
+                }
+                Err(err) => {
+                    let next = position + length;
+                    if next == bytes.len() || is_plausible_chunk(&bytes[next..]) {
+                        diagnostics.push(err.at(offset, index));
+                        position = next;
+                        chunk.reset_crc();
+                        chunks.push(chunk);
+                        continue;
+                    }
+                    err
+                }
+            },
+            Err(err) => err,
+        };
+        diagnostics.push(error.at(offset, index));
+
+        let skipped = (position + 1..bytes.len())
+            .find(|&start| is_plausible_chunk(&bytes[start..]))
+            .unwrap_or(bytes.len())
+            - position;
+        diagnostics.push(PngError::Garbage {
+            length: skipped as u64,
+            offset,
+            index,
+        });
+        position += skipped;
+    }
+
+    let mut png = Png::from_chunks(chunks);
+    png.set_trailing_bytes(trailing);
+    (png, diagnostics)
+}
+
+/// Whether `bytes` starts with a whole chunk with a valid CRC. This runs at
+/// every offset while skipping garbage, so it works on the borrowed bytes
+/// and never copies the data into a [`Chunk`].
+fn is_plausible_chunk(bytes: &[u8]) -> bool {
+    let Some(header) = bytes.get(..8) else {
+        return false;
+    };
+    let length = u32::from_be_bytes([header[0], header[1], header[2], header[3]]);
+    if length > Chunk::MAX_LENGTH || !header[4..].iter().all(u8::is_ascii_alphabetic) {
+        return false;
+    }
+    let end = 8 + length as usize;
+    match bytes.get(end..end + 4) {
+        Some(crc) => CHECKSUM.checksum(&bytes[4..end]).to_be_bytes() == crc,
+        None => false,
+    }
+}
@@ -0,0 +1,114 @@
+use super::*;
+use crate::test_utils;
+
+fn testing_png() -> Png {
+    Png::from_chunks(vec![
+        test_utils::chunk("FrSt", b"I am the first chunk"),
+        test_utils::chunk("miDl", b"I am another chunk"),
+        test_utils::chunk("LASt", b"I am the last chunk"),
+        test_utils::chunk("IEND", b""),
+    ])
+}
+
+fn chunk_types(png: &Png) -> Vec<String> {
+    png.chunks()
+        .iter()
+        .map(|chunk| chunk.chunk_type().to_string())
+        .collect()
+}
+
+#[test]
+fn test_undamaged() {
+    let bytes = testing_png().as_bytes();
+    let (png, diagnostics) = recover(&bytes);
+    assert!(diagnostics.is_empty());
+    assert_eq!(png.as_bytes(), bytes);
+}
+
+#[test]
+fn test_crc_mismatch_kept() {
+    let mut bytes = testing_png().as_bytes();
+    // last byte of the second chunk's crc
+    bytes[8 + 32 + 30 - 1] ^= 0xff;
+    let (png, diagnostics) = recover(&bytes);
+    assert_eq!(chunk_types(&png), ["FrSt", "miDl", "LASt", "IEND"]);
+    assert_eq!(diagnostics.len(), 1);
+    assert!(matches!(
+        diagnostics[0],
+        PngError::CrcMismatch {
+            offset: 40,
+            index: 1,
+            ..
+        }
+    ));
+    assert_eq!(png.as_bytes(), testing_png().as_bytes());
+}
+
+#[test]
+fn test_resync_after_bad_length() {
+    let mut bytes = testing_png().as_bytes();
+    // length of the second chunk
+    bytes[8 + 32 + 2] = 0xff;
+    let (png, diagnostics) = recover(&bytes);
+    assert_eq!(chunk_types(&png), ["FrSt", "LASt", "IEND"]);
+    assert!(matches!(
+        diagnostics[..],
+        [
+            PngError::TruncatedChunk { offset: 40, .. },
+            PngError::Garbage {
+                length: 30,
+                offset: 40,
+                index: 1
+            }
+        ]
+    ));
+}
+
+#[test]
+fn test_garbage_between_chunks() {
+    let png = testing_png();
+    let mut bytes = Png::STANDARD_HEADER.to_vec();
+    bytes.extend(png.chunks()[0].as_bytes());
+    bytes.extend_from_slice(b"\x00\x01 garbage \xff");
+    bytes.extend(png.chunks()[1].as_bytes());
+    let (recovered, diagnostics) = recover(&bytes);
+    assert_eq!(chunk_types(&recovered), ["FrSt", "miDl"]);
+    assert_eq!(diagnostics.len(), 2);
+    assert!(matches!(
+        diagnostics[1],
+        PngError::Garbage { length: 12, .. }
+    ));
+}
+
+#[test]
+fn test_fake_headers_in_garbage() {
+    let png = testing_png();
+    let mut bytes = Png::STANDARD_HEADER.to_vec();
+    bytes.extend(png.chunks()[0].as_bytes());
+    // headers that claim long chunks, one running past the end of the
+    // garbage and one with the wrong CRC
+    bytes.extend_from_slice(b"\x00\x00\xff\xffJUNK");
+    bytes.extend_from_slice(b"\x00\x00\x00\x02JUNKab\x00\x00\x00\x00");
+    bytes.extend(png.chunks()[1].as_bytes());
+    let (recovered, diagnostics) = recover(&bytes);
+    assert_eq!(chunk_types(&recovered), ["FrSt", "miDl"]);
+    assert!(matches!(
+        diagnostics[1],
+        PngError::Garbage { length: 22, .. }
+    ));
+}
+
+#[test]
+fn test_bad_signature_and_truncated_tail() {
+    let mut bytes = testing_png().as_bytes();
+    bytes[1] = 0;
+    bytes.truncate(bytes.len() - 20);
+    let (png, diagnostics) = recover(&bytes);
+    assert_eq!(chunk_types(&png), ["FrSt", "miDl"]);
+    assert!(matches!(diagnostics[0], PngError::BadSignature { .. }));
+    assert!(matches!(
+        diagnostics[1],
+        PngError::TruncatedChunk { index: 2, .. }
+    ));
+    assert!(png.trailing_bytes().is_empty());
+}
@@ -0,0 +1,447 @@
+//! What the standard chunk types are: where each is defined, how many of it
+//! a file may have, where it may go and what it holds. Covers PNG 1.2, the
+//! chunks added by the third edition, and the public chunks registered in
+//! the extensions to the spec.
+//! See [PNG 1.2](http://www.libpng.org/pub/png/spec/1.2/PNG-Chunks.html),
+//! [PNG third edition](https://www.w3.org/TR/png-3/) and
+//! [PNG extensions](http://www.libpng.org/pub/png/spec/register/pngext-1.5.0.html).
+
+#[cfg(test)]
+mod unit_tests;
+
+use std::fmt::Display;
+
+use crate::chunk_type::ChunkType;
+
+/// The document a chunk type is defined in.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Specification {
+    Png12,
+    ThirdEdition,
+    Extensions,
+}
+
+impl Display for Specification {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Specification::Png12 => "PNG 1.2",
+            Specification::ThirdEdition => "PNG third edition",
+            Specification::Extensions => "PNG extensions",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// How many chunks of a type a file may have.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Multiplicity {
+    Once,
+    Multiple,
+}
+
+impl Display for Multiplicity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Multiplicity::Once => write!(f, "at most one"),
+            Multiplicity::Multiple => write!(f, "any number"),
+        }
+    }
+}
+
+/// Where a chunk may go relative to the others.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Constraint {
+    First,
+    Last,
+    BeforePalette,
+    AfterPalette,
+    BeforeImageData,
+    AfterImageData,
+    /// Chunks of the type must follow each other with nothing in between
+    Consecutive,
+}
+
+impl Display for Constraint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let rule = match self {
+            Constraint::First => "must be the first chunk",
+            Constraint::Last => "must be the last chunk",
+            Constraint::BeforePalette => "must come before PLTE",
+            Constraint::AfterPalette => "must come after PLTE",
+            Constraint::BeforeImageData => "must come before the first IDAT",
+            Constraint::AfterImageData => "must come after the first IDAT",
+            Constraint::Consecutive => "must be consecutive",
+        };
+        write!(f, "{rule}")
+    }
+}
+
+/// Everything known about a standard chunk type.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ChunkInfo {
+    pub chunk_type: ChunkType,
+    pub name: &'static str,
+    pub specification: Specification,
+    /// Section of the specification that defines the chunk, for the chunk
+    /// types that have one
+    pub section: Option<&'static str>,
+    pub multiplicity: Multiplicity,
+    /// Ordering rules besides coming after IHDR and before IEND, which
+    /// apply to every chunk
+    pub constraints: &'static [Constraint],
+    pub description: &'static str,
+}
+
+impl ChunkInfo {
+    /// Whether chunks of this type must follow the rule
+    pub fn must(&self, constraint: Constraint) -> bool {
+        self.constraints.contains(&constraint)
+    }
+}
+
+use Constraint::*;
+use Multiplicity::*;
+use Specification::*;
+
+const COLOR_SPACE: &[Constraint] = &[BeforePalette, BeforeImageData];
+const PALETTE_DEPENDENT: &[Constraint] = &[AfterPalette, BeforeImageData];
+
+/// Every standard chunk type, critical chunks first
+pub const STANDARD_CHUNKS: [ChunkInfo; 34] = [
+    ChunkInfo {
+        chunk_type: ChunkType::IHDR,
+        name: "Image header",
+        specification: Png12,
+        section: Some("4.1.1"),
+        multiplicity: Once,
+        constraints: &[First],
+        description: "Width, height, bit depth, color type, compression method, filter method \
+                      and interlace method of the image.",
+    },
+    ChunkInfo {
+        chunk_type: ChunkType::PLTE,
+        name: "Palette",
+        specification: Png12,
+        section: Some("4.1.2"),
+        multiplicity: Once,
+        constraints: &[BeforeImageData],
+        description: "Between 1 and 256 RGB colors. Required for indexed color images, a \
+                      suggestion for truecolor ones and not allowed in grayscale ones.",
+    },
+    ChunkInfo {
+        chunk_type: ChunkType::IDAT,
+        name: "Image data",
+        specification: Png12,
+        section: Some("4.1.3"),
+        multiplicity: Multiple,
+        constraints: &[Consecutive],
+        description: "The filtered scanlines as one zlib stream, which may be split across \
+                      several chunks.",
+    },
+    ChunkInfo {
+        chunk_type: ChunkType::IEND,
+        name: "Image trailer",
+        specification: Png12,
+        section: Some("4.1.4"),
+        multiplicity: Once,
+        constraints: &[Last],
+        description: "Marks the end of the file. Its data is empty.",
+    },
+    ChunkInfo {
+        chunk_type: ChunkType::tRNS,
+        name: "Transparency",
+        specification: Png12,
+        section: Some("4.2.1.1"),
+        multiplicity: Once,
+        constraints: PALETTE_DEPENDENT,
+        description: "Alpha values for the palette entries, or the one color that is fully \
+                      transparent in grayscale and truecolor images.",
+    },
+    ChunkInfo {
+        chunk_type: ChunkType::gAMA,
+        name: "Image gamma",
+        specification: Png12,
+        section: Some("4.2.2.1"),
+        multiplicity: Once,
+        constraints: COLOR_SPACE,
+        description: "The gamma the image was encoded with, times 100000.",
+    },
+    ChunkInfo {
+        chunk_type: ChunkType::cHRM,
+        name: "Primary chromaticities",
+        specification: Png12,
+        section: Some("4.2.2.2"),
+        multiplicity: Once,
+        constraints: COLOR_SPACE,
+        description: "CIE x and y of the white point and the red, green and blue primaries, \
+                      times 100000.",
+    },
+    ChunkInfo {
+        chunk_type: ChunkType::sRGB,
+        name: "Standard RGB color space",
+        specification: Png12,
+        section: Some("4.2.2.3"),
+        multiplicity: Once,
+        constraints: COLOR_SPACE,
+        description: "The image is in the sRGB color space, shown with the given rendering \
+                      intent. Should not appear together with iCCP.",
+    },
+    ChunkInfo {
+        chunk_type: ChunkType::iCCP,
+        name: "Embedded ICC profile",
+        specification: Png12,
+        section: Some("4.2.2.4"),
+        multiplicity: Once,
+        constraints: COLOR_SPACE,
+        description: "A named, zlib compressed ICC color profile. Should not appear together \
+                      with sRGB.",
+    },
+    ChunkInfo {
+        chunk_type: ChunkType::tEXt,
+        name: "Textual data",
+        specification: Png12,
+        section: Some("4.2.3.1"),
+        multiplicity: Multiple,
+        constraints: &[],
+        description: "A keyword and uncompressed Latin-1 text.",
+    },
+    ChunkInfo {
+        chunk_type: ChunkType::zTXt,
+        name: "Compressed textual data",
+        specification: Png12,
+        section: Some("4.2.3.2"),
+        multiplicity: Multiple,
+        constraints: &[],
+        description: "A keyword and zlib compressed Latin-1 text.",
+    },
+    ChunkInfo {
+        chunk_type: ChunkType::iTXt,
+        name: "International textual data",
+        specification: Png12,
+        section: Some("4.2.3.3"),
+        multiplicity: Multiple,
+        constraints: &[],
+        description: "A keyword and UTF-8 text that may be compressed, with a language tag \
+                      and the keyword translated into that language.",
+    },
+    ChunkInfo {
+        chunk_type: ChunkType::bKGD,
+        name: "Background color",
+        specification: Png12,
+        section: Some("4.2.4.1"),
+        multiplicity: Once,
+        constraints: PALETTE_DEPENDENT,
+        description: "A color to show the image against.",
+    },
+    ChunkInfo {
+        chunk_type: ChunkType::pHYs,
+        name: "Physical pixel dimensions",
+        specification: Png12,
+        section: Some("4.2.4.2"),
+        multiplicity: Once,
+        constraints: &[BeforeImageData],
+        description: "Pixels per unit along each axis, either per metre or giving only the \
+                      aspect ratio.",
+    },
+    ChunkInfo {
+        chunk_type: ChunkType::sBIT,
+        name: "Significant bits",
+        specification: Png12,
+        section: Some("4.2.4.3"),
+        multiplicity: Once,
+        constraints: COLOR_SPACE,
+        description: "How many bits of each sample were significant in the original image.",
+    },
+    ChunkInfo {
+        chunk_type: ChunkType::sPLT,
+        name: "Suggested palette",
+        specification: Png12,
+        section: Some("4.2.4.4"),
+        multiplicity: Multiple,
+        constraints: &[BeforeImageData],
+        description: "A named palette for showing the image on displays with few colors.",
+    },
+    ChunkInfo {
+        chunk_type: ChunkType::hIST,
+        name: "Palette histogram",
+        specification: Png12,
+        section: Some("4.2.4.5"),
+        multiplicity: Once,
+        constraints: PALETTE_DEPENDENT,
+        description: "How often each palette color is used, approximately.",
+    },
+    ChunkInfo {
+        chunk_type: ChunkType::tIME,
+        name: "Image last-modification time",
+        specification: Png12,
+        section: Some("4.2.4.6"),
+        multiplicity: Once,
+        constraints: &[],
+        description: "When the image was last changed, in UTC.",
+    },
+    ChunkInfo {
+        chunk_type: ChunkType::cICP,
+        name: "Coding-independent code points",
+        specification: ThirdEdition,
+        section: Some("11.3.3.6"),
+        multiplicity: Once,
+        constraints: COLOR_SPACE,
+        description: "Color primaries, transfer function and matrix coefficients from ITU-T \
+                      H.273, used for HDR and wide gamut video color spaces.",
+    },
+    ChunkInfo {
+        chunk_type: ChunkType::mDCV,
+        name: "Mastering display color volume",
+        specification: ThirdEdition,
+        section: Some("11.3.3.7"),
+        multiplicity: Once,
+        constraints: COLOR_SPACE,
+        description: "Primaries, white point and luminance range of the display the image \
+                      was mastered on.",
+    },
+    ChunkInfo {
+        chunk_type: ChunkType::cLLI,
+        name: "Content light level information",
+        specification: ThirdEdition,
+        section: Some("11.3.3.8"),
+        multiplicity: Once,
+        constraints: COLOR_SPACE,
+        description: "The maximum and maximum frame average light levels of the image, for \
+                      HDR displays.",
+    },
+    ChunkInfo {
+        chunk_type: ChunkType::eXIf,
+        name: "Exchangeable image file profile",
+        specification: ThirdEdition,
+        section: Some("11.3.5.5"),
+        multiplicity: Once,
+        constraints: &[],
+        description: "Exif metadata such as camera settings, as stored in TIFF.",
+    },
+    ChunkInfo {
+        chunk_type: ChunkType::acTL,
+        name: "Animation control",
+        specification: ThirdEdition,
+        section: Some("11.3.7.1"),
+        multiplicity: Once,
+        constraints: &[BeforeImageData],
+        description: "Marks the file as an APNG, with the number of frames and how many \
+                      times to play them.",
+    },
+    ChunkInfo {
+        chunk_type: ChunkType::fcTL,
+        name: "Frame control",
+        specification: ThirdEdition,
+        section: Some("11.3.7.2"),
+        multiplicity: Multiple,
+        constraints: &[],
+        description: "Size, position, delay and dispose and blend operations of the next \
+                      animation frame.",
+    },
+    ChunkInfo {
+        chunk_type: ChunkType::fdAT,
+        name: "Frame data",
+        specification: ThirdEdition,
+        section: Some("11.3.7.3"),
+        multiplicity: Multiple,
+        constraints: &[AfterImageData],
+        description: "Image data of an animation frame, laid out like IDAT after a sequence \
+                      number.",
+    },
+    ChunkInfo {
+        chunk_type: ChunkType::oFFs,
+        name: "Image offset",
+        specification: Extensions,
+        section: Some("4.1"),
+        multiplicity: Once,
+        constraints: &[BeforeImageData],
+        description: "Where the image sits on a page, in pixels or micrometres.",
+    },
+    ChunkInfo {
+        chunk_type: ChunkType::pCAL,
+        name: "Calibration of pixel values",
+        specification: Extensions,
+        section: Some("4.2"),
+        multiplicity: Once,
+        constraints: &[BeforeImageData],
+        description: "An equation mapping sample values to physical values, such as \
+                      temperatures or heights.",
+    },
+    ChunkInfo {
+        chunk_type: ChunkType::sCAL,
+        name: "Physical scale of image subject",
+        specification: Extensions,
+        section: Some("4.3"),
+        multiplicity: Once,
+        constraints: &[BeforeImageData],
+        description: "The physical size covered by each pixel, in metres or radians.",
+    },
+    ChunkInfo {
+        chunk_type: ChunkType::gIFg,
+        name: "GIF graphic control extension",
+        specification: Extensions,
+        section: Some("4.4"),
+        multiplicity: Multiple,
+        constraints: &[],
+        description: "Disposal method, user input flag and delay carried over from a GIF.",
+    },
+    ChunkInfo {
+        chunk_type: ChunkType::gIFx,
+        name: "GIF application extension",
+        specification: Extensions,
+        section: Some("4.5"),
+        multiplicity: Multiple,
+        constraints: &[],
+        description: "An application extension block carried over from a GIF.",
+    },
+    ChunkInfo {
+        chunk_type: ChunkType::gIFt,
+        name: "GIF plain text extension",
+        specification: Extensions,
+        section: None,
+        multiplicity: Multiple,
+        constraints: &[],
+        description: "Text to draw over the image, carried over from a GIF. Deprecated.",
+    },
+    ChunkInfo {
+        chunk_type: ChunkType::sTER,
+        name: "Indicator of stereo image",
+        specification: Extensions,
+        section: Some("4.6"),
+        multiplicity: Once,
+        constraints: &[BeforeImageData],
+        description: "The image holds a left and a right eye view side by side, for cross \
+                      or divergent fusing.",
+    },
+    ChunkInfo {
+        chunk_type: ChunkType::dSIG,
+        name: "Digital signature",
+        specification: Extensions,
+        section: None,
+        multiplicity: Multiple,
+        constraints: &[],
+        description: "A digital signature of the chunks between a pair of dSIG chunks.",
+    },
+    ChunkInfo {
+        chunk_type: ChunkType::fRAc,
+        name: "Fractal image parameters",
+        specification: Extensions,
+        section: None,
+        multiplicity: Multiple,
+        constraints: &[],
+        description: "Registered for parameters of fractal images, but never specified.",
+    },
+];
+
+/// What is known about a chunk type, if it is a standard one
+pub fn lookup(chunk_type: &ChunkType) -> Option<&'static ChunkInfo> {
+    STANDARD_CHUNKS
+        .iter()
+        .find(|info| info.chunk_type == *chunk_type)
+}
+
+/// Whether chunks of this type must follow the rule. Unknown chunk types
+/// only have to come between IHDR and IEND.
+pub fn must(chunk_type: &ChunkType, constraint: Constraint) -> bool {
+    lookup(chunk_type).is_some_and(|info| info.must(constraint))
+}
@@ -0,0 +1,59 @@
+use super::*;
+use std::str::FromStr;
+
+#[test]
+fn test_lookup() {
+    let info = lookup(&ChunkType::from_str("tEXt").unwrap()).unwrap();
+    assert_eq!(info.chunk_type, ChunkType::tEXt);
+    assert_eq!(info.name, "Textual data");
+    assert_eq!(info.specification, Specification::Png12);
+    assert_eq!(info.multiplicity, Multiplicity::Multiple);
+    assert!(lookup(&ChunkType::from_str("ruSt").unwrap()).is_none());
+}
+
+#[test]
+fn test_must() {
+    assert!(must(&ChunkType::gAMA, Constraint::BeforePalette));
+    assert!(must(&ChunkType::tRNS, Constraint::AfterPalette));
+    assert!(must(&ChunkType::fdAT, Constraint::AfterImageData));
+    assert!(!must(&ChunkType::tEXt, Constraint::BeforeImageData));
+    assert!(!must(
+        &ChunkType::from_str("ruSt").unwrap(),
+        Constraint::BeforeImageData
+    ));
+}
+
+#[test]
+fn test_registry_is_consistent() {
+    for (index, info) in STANDARD_CHUNKS.iter().enumerate() {
+        let chunk_type = info.chunk_type;
+        assert!(chunk_type.is_valid(), "{chunk_type}");
+        // The APNG chunks were named before they were standardised, and
+        // kept their private looking names
+        let animation = [ChunkType::acTL, ChunkType::fcTL, ChunkType::fdAT];
+        assert_eq!(
+            chunk_type.is_public(),
+            !animation.contains(&chunk_type),
+            "{chunk_type}"
+        );
+        assert_eq!(
+            chunk_type.is_critical(),
+            index < 4,
+            "{chunk_type} is listed with the wrong chunks"
+        );
+        assert_eq!(
+            STANDARD_CHUNKS
+                .iter()
+                .filter(|other| other.chunk_type == chunk_type)
+                .count(),
+            1,
+            "{chunk_type} is listed twice"
+        );
+        if info.specification != Specification::Extensions {
+            assert!(info.section.is_some(), "{chunk_type} has no section");
+        }
+        if info.must(Constraint::BeforePalette) {
+            assert!(info.must(Constraint::BeforeImageData), "{chunk_type}");
+        }
+    }
+}
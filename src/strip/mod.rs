@@ -0,0 +1,115 @@
+//! Removing metadata. Critical chunks are always kept, as are the
+//! ancillary chunks that are part of the image itself rather than
+//! information about it: tRNS, which makes pixels transparent, and the
+//! APNG animation chunks. Every other ancillary chunk is removed unless a
+//! [`Policy`] allows it by type, or for text chunks by keyword.
+
+#[cfg(test)]
+mod unit_tests;
+
+use std::fmt::Display;
+
+use crate::{chunk::Chunk, chunk_type::ChunkType, png::Png, text::TextChunk};
+
+/// Ancillary chunks that change what the image looks like
+pub const IMAGE_CHUNKS: [ChunkType; 4] = [
+    ChunkType::tRNS,
+    ChunkType::acTL,
+    ChunkType::fcTL,
+    ChunkType::fdAT,
+];
+/// Ancillary chunks that say how to display colors
+pub const COLOR_MANAGEMENT_CHUNKS: [ChunkType; 8] = [
+    ChunkType::cHRM,
+    ChunkType::gAMA,
+    ChunkType::iCCP,
+    ChunkType::sBIT,
+    ChunkType::sRGB,
+    ChunkType::cICP,
+    ChunkType::mDCV,
+    ChunkType::cLLI,
+];
+
+/// Which ancillary chunks to keep.
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct Policy {
+    /// Chunk types to keep
+    pub keep_types: Vec<ChunkType>,
+    /// Keywords of text chunks to keep
+    pub keep_keywords: Vec<String>,
+}
+
+impl Policy {
+    /// Remove every ancillary chunk that isn't part of the image
+    pub fn all_ancillary() -> Policy {
+        Policy::default()
+    }
+    /// Remove metadata but keep the chunks that say how to display colors
+    pub fn keep_color_management() -> Policy {
+        Policy::default().keeping_types(COLOR_MANAGEMENT_CHUNKS)
+    }
+    /// Remove metadata but keep text chunks with these keywords
+    pub fn keep_text<S: Into<String>>(keywords: impl IntoIterator<Item = S>) -> Policy {
+        Policy::default().keeping_keywords(keywords)
+    }
+    /// Also keep chunks of these types
+    pub fn keeping_types(mut self, chunk_types: impl IntoIterator<Item = ChunkType>) -> Policy {
+        self.keep_types.extend(chunk_types);
+        self
+    }
+    /// Also keep text chunks with these keywords
+    pub fn keeping_keywords<S: Into<String>>(
+        mut self,
+        keywords: impl IntoIterator<Item = S>,
+    ) -> Policy {
+        self.keep_keywords
+            .extend(keywords.into_iter().map(Into::into));
+        self
+    }
+    /// Whether a chunk survives stripping
+    pub fn keeps(&self, chunk: &Chunk) -> bool {
+        let chunk_type = chunk.chunk_type();
+        chunk_type.is_critical()
+            || IMAGE_CHUNKS.contains(chunk_type)
+            || self.keep_types.contains(chunk_type)
+            || TextChunk::keyword_of(chunk)
+                .is_some_and(|keyword| self.keep_keywords.contains(&keyword))
+    }
+}
+
+/// A chunk that stripping removes.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Removal {
+    /// Index of the chunk before anything was removed
+    pub index: usize,
+    pub chunk_type: ChunkType,
+    /// Keyword, for text chunks
+    pub keyword: Option<String>,
+    /// Bytes saved by removing the chunk, including its length, type and CRC
+    pub length: u64,
+}
+
+impl Display for Removal {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "chunk {} {}", self.index, self.chunk_type)?;
+        if let Some(keyword) = &self.keyword {
+            write!(f, " ({keyword})")?;
+        }
+        write!(f, ", {} bytes", self.length)
+    }
+}
+
+/// The chunks a policy would remove from a png, without removing them
+pub fn removals(png: &Png, policy: &Policy) -> Vec<Removal> {
+    png.chunks()
+        .iter()
+        .enumerate()
+        .filter(|(_, chunk)| !policy.keeps(chunk))
+        .map(|(index, chunk)| Removal {
+            index,
+            chunk_type: *chunk.chunk_type(),
+            keyword: TextChunk::keyword_of(chunk),
+            length: u64::from(chunk.length()) + 12,
+        })
+        .collect()
+}
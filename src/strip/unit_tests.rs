@@ -0,0 +1,66 @@
+use super::*;
+use crate::test_utils::chunk;
+
+fn testing_png() -> Png {
+    Png::from_chunks(vec![
+        chunk("IHDR", &[0, 0, 0, 1, 0, 0, 0, 1, 8, 0, 0, 0, 0]),
+        chunk("sRGB", &[0]),
+        chunk("gAMA", &[0, 0, 177, 143]),
+        chunk("tEXt", b"Title\0Dice"),
+        chunk("tEXt", b"Author\0Someone"),
+        chunk("tIME", &[7, 234, 10, 16, 12, 0, 0]),
+        chunk("tRNS", &[0, 0]),
+        chunk("IDAT", &[]),
+        chunk("eXIf", b"MM\0*"),
+        chunk("IEND", &[]),
+    ])
+}
+
+fn remaining(png: &Png) -> String {
+    png.to_string().replace("\r\n", " ").trim_end().to_string()
+}
+
+#[test]
+fn test_all_ancillary() {
+    let mut png = testing_png();
+    let removals = png.strip(&Policy::all_ancillary());
+    assert_eq!(removals.len(), 6);
+    assert_eq!(remaining(&png), "IHDR tRNS IDAT IEND");
+}
+
+#[test]
+fn test_keep_color_management() {
+    let mut png = testing_png();
+    png.strip(&Policy::keep_color_management());
+    assert_eq!(remaining(&png), "IHDR sRGB gAMA tRNS IDAT IEND");
+}
+
+#[test]
+fn test_keep_text() {
+    let mut png = testing_png();
+    png.strip(&Policy::keep_text(["Title"]));
+    assert_eq!(remaining(&png), "IHDR tEXt tRNS IDAT IEND");
+    assert_eq!(png.text("Title").unwrap().unwrap().text, "Dice");
+}
+
+#[test]
+fn test_keeping_types() {
+    let mut png = testing_png();
+    let policy = Policy::keep_text(["Author"]).keeping_types([ChunkType::eXIf]);
+    png.strip(&policy);
+    assert_eq!(remaining(&png), "IHDR tEXt tRNS IDAT eXIf IEND");
+}
+
+#[test]
+fn test_dry_run_matches_strip() {
+    let png = testing_png();
+    let planned = removals(&png, &Policy::keep_color_management());
+    assert_eq!(png.chunks().len(), 10);
+    let mut stripped = testing_png();
+    assert_eq!(stripped.strip(&Policy::keep_color_management()), planned);
+
+    assert_eq!(planned[0].to_string(), "chunk 3 tEXt (Title), 22 bytes");
+    assert_eq!(planned[2].to_string(), "chunk 5 tIME, 19 bytes");
+    let saved: u64 = planned.iter().map(|removal| removal.length).sum();
+    assert_eq!(saved, png.byte_len() - stripped.byte_len());
+}
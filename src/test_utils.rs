@@ -0,0 +1,27 @@
+//! Fixtures shared by the unit tests.
+
+use std::str::FromStr;
+
+use crate::{
+    chunk::Chunk,
+    chunk_type::ChunkType,
+    image_header::{ColorType, ImageHeader, InterlaceMethod},
+};
+
+/// A chunk of the named type holding `data`
+pub fn chunk(chunk_type: &str, data: &[u8]) -> Chunk {
+    Chunk::new(ChunkType::from_str(chunk_type).unwrap(), data.to_vec())
+}
+
+/// The header of a non-interlaced image
+pub fn header(width: u32, height: u32, bit_depth: u8, color_type: ColorType) -> ImageHeader {
+    ImageHeader {
+        width,
+        height,
+        bit_depth,
+        color_type,
+        compression_method: 0,
+        filter_method: 0,
+        interlace_method: InterlaceMethod::None,
+    }
+}
@@ -0,0 +1,250 @@
+//! Textual information stored in tEXt, zTXt and iTXt chunks.
+//!
+//! Each text chunk holds a keyword that says what the text is, such as
+//! `Title`, `Author` or `Comment`, followed by the text itself:
+//!
+//! - **tEXt**: `keyword\0text`, both in Latin-1 (ISO 8859-1)
+//! - **zTXt**: `keyword\0` compression method, then zlib compressed Latin-1
+//!   text
+//! - **iTXt**: `keyword\0` compression flag and method, `language tag\0`,
+//!   `translated keyword\0`, then UTF-8 text that may be compressed
+//!
+//! Keywords are 1-79 Latin-1 characters without leading, trailing or
+//! consecutive spaces. See [Textual information](http://www.libpng.org/pub/png/spec/1.2/PNG-Chunks.html#C.Anc-text).
+
+#[cfg(test)]
+mod unit_tests;
+
+use std::{fmt::Display, str};
+
+use crate::{chunk::Chunk, chunk_type::ChunkType, error::PngError, zlib};
+
+/// Compressed text is not inflated past this many bytes, so that a small
+/// chunk can't expand to fill memory
+pub const MAX_TEXT_LENGTH: u64 = 16 * 1024 * 1024;
+
+/// Which of the three text chunk types a [`TextChunk`] is stored as.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum TextKind {
+    /// tEXt, uncompressed Latin-1
+    Latin1,
+    /// zTXt, compressed Latin-1
+    Compressed,
+    /// iTXt, UTF-8 text with a language tag and translated keyword
+    International {
+        compressed: bool,
+        /// An RFC 3066 language tag such as `en-GB`, or empty if unknown
+        language_tag: String,
+        /// The keyword translated into the language of the text
+        translated_keyword: String,
+    },
+}
+
+/// A decoded text chunk.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct TextChunk {
+    pub keyword: String,
+    pub text: String,
+    pub kind: TextKind,
+}
+
+impl TextChunk {
+    /// Chunk types that hold text
+    pub const CHUNK_TYPES: [ChunkType; 3] = [ChunkType::tEXt, ChunkType::zTXt, ChunkType::iTXt];
+
+    /// An uncompressed tEXt chunk
+    pub fn new(keyword: &str, text: &str) -> Self {
+        TextChunk {
+            keyword: keyword.to_string(),
+            text: text.to_string(),
+            kind: TextKind::Latin1,
+        }
+    }
+    /// Whether a chunk is one of the text chunk types
+    pub fn is_text_chunk(chunk: &Chunk) -> bool {
+        TextChunk::CHUNK_TYPES.contains(chunk.chunk_type())
+    }
+    /// The keyword of a text chunk, without decoding the rest of it
+    pub fn keyword_of(chunk: &Chunk) -> Option<String> {
+        if !TextChunk::is_text_chunk(chunk) {
+            return None;
+        }
+        split_null(chunk.data()).map(|(keyword, _)| latin1_decode(keyword))
+    }
+    /// The chunk type this text is stored in
+    pub fn chunk_type(&self) -> ChunkType {
+        match self.kind {
+            TextKind::Latin1 => ChunkType::tEXt,
+            TextKind::Compressed => ChunkType::zTXt,
+            TextKind::International { .. } => ChunkType::iTXt,
+        }
+    }
+    /// Encode into a chunk, checking the keyword and that tEXt and zTXt text
+    /// can be written as Latin-1
+    pub fn to_chunk(&self) -> Result<Chunk, PngError> {
+        validate_keyword(&self.keyword)?;
+        if !matches!(self.kind, TextKind::International { .. }) && self.text.contains('\0') {
+            return Err(invalid("tEXt and zTXt text can't contain null"));
+        }
+        let mut data = latin1_encode(&self.keyword)?;
+        data.push(0);
+        match &self.kind {
+            TextKind::Latin1 => data.extend(latin1_encode(&self.text)?),
+            TextKind::Compressed => {
+                data.push(0);
+                data.extend(deflate(&latin1_encode(&self.text)?));
+            }
+            TextKind::International {
+                compressed,
+                language_tag,
+                translated_keyword,
+            } => {
+                if language_tag.contains('\0') || translated_keyword.contains('\0') {
+                    return Err(invalid(
+                        "language tag and translated keyword can't contain null",
+                    ));
+                }
+                data.extend([u8::from(*compressed), 0]);
+                data.extend(language_tag.as_bytes());
+                data.push(0);
+                data.extend(translated_keyword.as_bytes());
+                data.push(0);
+                match compressed {
+                    true => data.extend(deflate(self.text.as_bytes())),
+                    false => data.extend(self.text.as_bytes()),
+                }
+            }
+        }
+        Ok(Chunk::new(self.chunk_type(), data))
+    }
+}
+
+impl TryFrom<&Chunk> for TextChunk {
+    type Error = PngError;
+
+    /// Decode a tEXt, zTXt or iTXt chunk
+    fn try_from(chunk: &Chunk) -> Result<Self, Self::Error> {
+        let (keyword, rest) = split_null(chunk.data())
+            .ok_or_else(|| invalid("no null separator after the keyword"))?;
+        let keyword = latin1_decode(keyword);
+        validate_keyword(&keyword)?;
+
+        let (text, kind) = match *chunk.chunk_type() {
+            ChunkType::tEXt => (latin1_decode(rest), TextKind::Latin1),
+            ChunkType::zTXt => match rest {
+                [0, compressed @ ..] => {
+                    (latin1_decode(&inflate(compressed)?), TextKind::Compressed)
+                }
+                [method, ..] => {
+                    return Err(invalid(format!("unknown compression method {method}")))
+                }
+                [] => return Err(invalid("missing compression method")),
+            },
+            ChunkType::iTXt => {
+                let [flag, method, rest @ ..] = rest else {
+                    return Err(invalid("missing compression flag and method"));
+                };
+                let compressed = match (flag, method) {
+                    (0, _) => false,
+                    (1, 0) => true,
+                    (1, method) => {
+                        return Err(invalid(format!("unknown compression method {method}")))
+                    }
+                    (flag, _) => return Err(invalid(format!("invalid compression flag {flag}"))),
+                };
+                let (language_tag, rest) =
+                    split_null(rest).ok_or_else(|| invalid("no null after the language tag"))?;
+                let (translated_keyword, text) = split_null(rest)
+                    .ok_or_else(|| invalid("no null after the translated keyword"))?;
+                let text = match compressed {
+                    true => inflate(text)?,
+                    false => text.to_vec(),
+                };
+                (
+                    utf8(text)?,
+                    TextKind::International {
+                        compressed,
+                        language_tag: utf8(language_tag.to_vec())?,
+                        translated_keyword: utf8(translated_keyword.to_vec())?,
+                    },
+                )
+            }
+            _ => {
+                return Err(invalid(format!(
+                    "{} is not a text chunk",
+                    chunk.chunk_type()
+                )))
+            }
+        };
+        Ok(TextChunk {
+            keyword,
+            text,
+            kind,
+        })
+    }
+}
+
+impl Display for TextChunk {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.keyword, self.text)
+    }
+}
+
+fn invalid(reason: impl Into<String>) -> PngError {
+    PngError::InvalidText {
+        reason: reason.into(),
+        offset: 0,
+        index: 0,
+    }
+}
+
+/// Split at the first null byte, dropping the null
+fn split_null(bytes: &[u8]) -> Option<(&[u8], &[u8])> {
+    let position = bytes.iter().position(|&byte| byte == 0)?;
+    Some((&bytes[..position], &bytes[position + 1..]))
+}
+
+/// Every byte maps to the unicode code point with the same value
+fn latin1_decode(bytes: &[u8]) -> String {
+    bytes.iter().map(|&byte| char::from(byte)).collect()
+}
+
+fn latin1_encode(text: &str) -> Result<Vec<u8>, PngError> {
+    text.chars()
+        .map(|c| u8::try_from(c).map_err(|_| invalid(format!("{c:?} can't be written as Latin-1"))))
+        .collect()
+}
+
+fn utf8(bytes: Vec<u8>) -> Result<String, PngError> {
+    String::from_utf8(bytes).map_err(|err| invalid(format!("not valid utf-8: {err}")))
+}
+
+fn validate_keyword(keyword: &str) -> Result<(), PngError> {
+    if keyword.is_empty() || keyword.chars().count() > 79 {
+        return Err(invalid(format!(
+            "keyword {keyword:?} must be 1 to 79 characters"
+        )));
+    }
+    if keyword.starts_with(' ') || keyword.ends_with(' ') || keyword.contains("  ") {
+        return Err(invalid(format!(
+            "keyword {keyword:?} has leading, trailing or consecutive spaces"
+        )));
+    }
+    if let Some(bad) = keyword
+        .chars()
+        .find(|&c| !matches!(u32::from(c), 32..=126 | 161..=255))
+    {
+        return Err(invalid(format!(
+            "keyword {keyword:?} contains {bad:?}, which is not printable Latin-1"
+        )));
+    }
+    Ok(())
+}
+
+fn deflate(bytes: &[u8]) -> Vec<u8> {
+    zlib::deflate(bytes, 6)
+}
+
+fn inflate(bytes: &[u8]) -> Result<Vec<u8>, PngError> {
+    zlib::inflate(bytes, MAX_TEXT_LENGTH)
+}
@@ -0,0 +1,147 @@
+use super::*;
+use crate::test_utils;
+
+#[test]
+fn test_text_from_chunk() {
+    let chunk = test_utils::chunk("tEXt", b"Author\0Ren\xe9e");
+    let text = TextChunk::try_from(&chunk).unwrap();
+    assert_eq!(text, TextChunk::new("Author", "Renée"));
+}
+
+#[test]
+fn test_text_round_trip() {
+    let text = TextChunk::new("Comment", "Caf\u{e9} au lait");
+    let chunk = text.to_chunk().unwrap();
+    assert_eq!(chunk.chunk_type().to_string(), "tEXt");
+    assert_eq!(chunk.data(), b"Comment\0Caf\xe9 au lait");
+    assert_eq!(TextChunk::try_from(&chunk).unwrap(), text);
+}
+
+#[test]
+fn test_compressed_round_trip() {
+    let text = TextChunk {
+        keyword: "Description".to_string(),
+        text: "a long description ".repeat(20),
+        kind: TextKind::Compressed,
+    };
+    let chunk = text.to_chunk().unwrap();
+    assert_eq!(chunk.chunk_type().to_string(), "zTXt");
+    assert!(chunk.data().len() < text.text.len());
+    assert_eq!(TextChunk::try_from(&chunk).unwrap(), text);
+}
+
+#[test]
+fn test_international_round_trip() {
+    for compressed in [false, true] {
+        let text = TextChunk {
+            keyword: "Title".to_string(),
+            text: "サイコロ".to_string(),
+            kind: TextKind::International {
+                compressed,
+                language_tag: "ja".to_string(),
+                translated_keyword: "タイトル".to_string(),
+            },
+        };
+        let chunk = text.to_chunk().unwrap();
+        assert_eq!(chunk.chunk_type().to_string(), "iTXt");
+        assert_eq!(TextChunk::try_from(&chunk).unwrap(), text);
+    }
+}
+
+#[test]
+fn test_international_from_chunk() {
+    let chunk = test_utils::chunk("iTXt", b"Title\0\0\0en-GB\0Title\0Dice");
+    let text = TextChunk::try_from(&chunk).unwrap();
+    assert_eq!(text.text, "Dice");
+    assert_eq!(
+        text.kind,
+        TextKind::International {
+            compressed: false,
+            language_tag: "en-GB".to_string(),
+            translated_keyword: "Title".to_string(),
+        }
+    );
+}
+
+#[test]
+fn test_latin1_only() {
+    let text = TextChunk::new("Title", "サイコロ");
+    assert!(matches!(text.to_chunk(), Err(PngError::InvalidText { .. })));
+}
+
+#[test]
+fn test_invalid_keywords() {
+    for keyword in ["", " Title", "Title ", "Two  spaces", &"k".repeat(80)] {
+        assert!(
+            TextChunk::new(keyword, "text").to_chunk().is_err(),
+            "{keyword:?}"
+        );
+    }
+    assert!(TextChunk::new(&"k".repeat(79), "text").to_chunk().is_ok());
+}
+
+#[test]
+fn test_unprintable_keywords() {
+    for keyword in [
+        "Tab\there",
+        "New\nline",
+        "Del\u{7f}",
+        "No\u{a0}break",
+        "Null\0",
+    ] {
+        assert!(
+            matches!(
+                TextChunk::new(keyword, "text").to_chunk(),
+                Err(PngError::InvalidText { .. })
+            ),
+            "{keyword:?}"
+        );
+    }
+    assert!(TextChunk::new("Caf\u{e9} \u{a1}", "text")
+        .to_chunk()
+        .is_ok());
+    let chunk = test_utils::chunk("tEXt", b"Bell\x07\0text");
+    assert!(TextChunk::try_from(&chunk).is_err());
+}
+
+#[test]
+fn test_null_in_text() {
+    let mut text = TextChunk::new("Comment", "before\0after");
+    assert!(matches!(text.to_chunk(), Err(PngError::InvalidText { .. })));
+    text.kind = TextKind::Compressed;
+    assert!(matches!(text.to_chunk(), Err(PngError::InvalidText { .. })));
+}
+
+#[test]
+fn test_invalid_chunks() {
+    for (chunk_type, data) in [
+        ("tEXt", &b"no separator"[..]),
+        ("zTXt", b"Title\0\x01compressed"),
+        ("zTXt", b"Title\0\0not zlib"),
+        ("iTXt", b"Title\0\x02\0\0\0text"),
+        ("iTXt", b"Title\0\0\0en"),
+        ("RuSt", b"Title\0text"),
+    ] {
+        let chunk = test_utils::chunk(chunk_type, data);
+        assert!(
+            TextChunk::try_from(&chunk).is_err(),
+            "{chunk_type} {data:?}"
+        );
+    }
+}
+
+#[test]
+fn test_decompression_limit() {
+    let mut data = b"Bomb\0\0".to_vec();
+    data.extend(deflate(&vec![b'a'; MAX_TEXT_LENGTH as usize + 1]));
+    let chunk = test_utils::chunk("zTXt", &data);
+    assert!(TextChunk::try_from(&chunk).is_err());
+}
+
+#[test]
+fn test_keyword_of() {
+    let chunk = test_utils::chunk("zTXt", b"Title\0\0garbage");
+    assert_eq!(TextChunk::keyword_of(&chunk).as_deref(), Some("Title"));
+    let chunk = test_utils::chunk("RuSt", b"Title\0text");
+    assert_eq!(TextChunk::keyword_of(&chunk), None);
+}
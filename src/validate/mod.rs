@@ -0,0 +1,254 @@
+//! Checks a list of chunks against the ordering and multiplicity rules of
+//! the PNG spec. See [Chunk ordering rules](http://www.libpng.org/pub/png/spec/1.2/PNG-Chunks.html#C.Summary-of-standard-chunks).
+//!
+//! Critical chunks (must appear in this order, except PLTE is optional):
+//!
+//! | Name | Multiple OK? | Ordering constraints                     |
+//! |------|--------------|------------------------------------------|
+//! | IHDR | No           | Must be first                            |
+//! | PLTE | No           | Before first IDAT                        |
+//! | IDAT | Yes          | Multiple IDATs must be consecutive       |
+//! | IEND | No           | Must be last                             |
+//!
+//! Ancillary chunks that describe color (cHRM, gAMA, iCCP, sBIT, sRGB) must
+//! come before PLTE and IDAT, the palette dependent ones (bKGD, hIST, tRNS)
+//! after PLTE and before IDAT, and pHYs, sPLT and friends before IDAT. Text
+//! and time chunks can go anywhere. The rules for each chunk type come from
+//! [`crate::registry`].
+
+#[cfg(test)]
+mod unit_tests;
+
+use std::fmt::Display;
+
+use crate::{
+    chunk::Chunk,
+    chunk_type::ChunkType,
+    error::PngError,
+    image_header::{ColorType, ImageHeader},
+    registry::{self, Constraint, Multiplicity},
+};
+
+/// The rule a chunk breaks.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum ViolationKind {
+    /// The file has no IHDR chunk, or it is not the first chunk
+    HeaderNotFirst,
+    /// The IHDR chunk data is invalid
+    InvalidHeader(String),
+    /// The file has no IEND chunk, or it is not the last chunk
+    EndNotLast,
+    /// The file has no IDAT chunk
+    MissingData,
+    /// An IDAT chunk separated from the previous IDAT by other chunks
+    DataNotConsecutive,
+    /// A second copy of a chunk that may only appear once
+    Duplicate(String),
+    /// A chunk that must come before the named chunk type
+    MustPrecede(String, &'static str),
+    /// A chunk that must come after the named chunk type
+    MustFollow(String, &'static str),
+    /// An indexed color image without a PLTE chunk
+    MissingPalette,
+    /// A PLTE chunk in a grayscale image
+    UnexpectedPalette,
+    /// A chunk length above 2^31 - 1
+    LengthOverflow(u32),
+    /// A chunk type with a lowercase third letter
+    ReservedBit(String),
+}
+
+impl Display for ViolationKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ViolationKind::HeaderNotFirst => write!(f, "IHDR must be the first chunk"),
+            ViolationKind::InvalidHeader(reason) => write!(f, "invalid IHDR: {reason}"),
+            ViolationKind::EndNotLast => write!(f, "IEND must be the last chunk"),
+            ViolationKind::MissingData => write!(f, "no IDAT chunk"),
+            ViolationKind::DataNotConsecutive => write!(f, "IDAT chunks must be consecutive"),
+            ViolationKind::Duplicate(chunk_type) => {
+                write!(f, "{chunk_type} may only appear once")
+            }
+            ViolationKind::MustPrecede(chunk_type, other) => {
+                write!(f, "{chunk_type} must come before {other}")
+            }
+            ViolationKind::MustFollow(chunk_type, other) => {
+                write!(f, "{chunk_type} must come after {other}")
+            }
+            ViolationKind::MissingPalette => write!(f, "indexed color image has no PLTE"),
+            ViolationKind::UnexpectedPalette => write!(f, "grayscale image must not have PLTE"),
+            ViolationKind::LengthOverflow(length) => {
+                write!(f, "length {length} is more than the maximum of 2^31 - 1")
+            }
+            ViolationKind::ReservedBit(chunk_type) => {
+                write!(f, "{chunk_type} has the reserved bit set")
+            }
+        }
+    }
+}
+
+/// A broken rule and the chunk it was found at. Problems with the file as a
+/// whole, like a missing IEND, are reported at the end of the file.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Violation {
+    pub index: usize,
+    pub offset: u64,
+    pub kind: ViolationKind,
+}
+
+impl Display for Violation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "chunk {} at byte {}: {}",
+            self.index, self.offset, self.kind
+        )
+    }
+}
+
+/// Check a list of chunks and report every rule it breaks, in file order
+pub fn validate(chunks: &[Chunk]) -> Vec<Violation> {
+    let mut violations = Vec::new();
+    let position = |chunk_type: ChunkType| {
+        chunks
+            .iter()
+            .position(|chunk| *chunk.chunk_type() == chunk_type)
+    };
+    let first_plte = position(ChunkType::PLTE);
+    let first_idat = position(ChunkType::IDAT);
+
+    // offset of every chunk, plus the end of the file
+    let offsets: Vec<u64> = std::iter::once(8)
+        .chain(chunks.iter().scan(8, |offset, chunk| {
+            *offset += u64::from(chunk.length()) + 12;
+            Some(*offset)
+        }))
+        .collect();
+    let mut report = |index: usize, kind| {
+        violations.push(Violation {
+            index,
+            offset: offsets[index],
+            kind,
+        })
+    };
+
+    let mut seen: Vec<ChunkType> = Vec::new();
+    for (index, chunk) in chunks.iter().enumerate() {
+        let mut report = |kind| report(index, kind);
+        let chunk_type = *chunk.chunk_type();
+        let name = chunk_type.to_string();
+        let must = |constraint| registry::must(&chunk_type, constraint);
+
+        if must(Constraint::First) && index != 0 {
+            report(ViolationKind::HeaderNotFirst);
+        }
+        if must(Constraint::Last) && index != chunks.len() - 1 {
+            report(ViolationKind::EndNotLast);
+        }
+        if must(Constraint::Consecutive)
+            && seen.contains(&chunk_type)
+            && *chunks[index - 1].chunk_type() != chunk_type
+        {
+            report(ViolationKind::DataNotConsecutive);
+        }
+        if chunk_type == ChunkType::IHDR && index == 0 {
+            if let Err(err) = ImageHeader::try_from(chunk.data()) {
+                report(ViolationKind::InvalidHeader(header_reason(err)));
+            }
+        }
+        if index == 0 && chunk_type != ChunkType::IHDR {
+            report(ViolationKind::HeaderNotFirst);
+        }
+        let single = registry::lookup(&chunk_type)
+            .is_some_and(|info| info.multiplicity == Multiplicity::Once);
+        if single && seen.contains(&chunk_type) {
+            report(ViolationKind::Duplicate(name.clone()));
+        }
+        if must(Constraint::BeforePalette) && first_plte.is_some_and(|plte| plte < index) {
+            report(ViolationKind::MustPrecede(name.clone(), "PLTE"));
+        }
+        if must(Constraint::BeforeImageData) && first_idat.is_some_and(|idat| idat < index) {
+            report(ViolationKind::MustPrecede(name.clone(), "IDAT"));
+        }
+        if must(Constraint::AfterPalette) && first_plte.is_some_and(|plte| plte > index) {
+            report(ViolationKind::MustFollow(name.clone(), "PLTE"));
+        }
+        if must(Constraint::AfterImageData) && first_idat.is_some_and(|idat| idat > index) {
+            report(ViolationKind::MustFollow(name.clone(), "IDAT"));
+        }
+        if chunk.length() > Chunk::MAX_LENGTH {
+            report(ViolationKind::LengthOverflow(chunk.length()));
+        }
+        if !chunk.chunk_type().is_reserved_bit_valid() {
+            report(ViolationKind::ReservedBit(name));
+        }
+
+        seen.push(chunk_type);
+    }
+
+    let end = chunks.len();
+    if chunks.is_empty() {
+        report(end, ViolationKind::HeaderNotFirst);
+    }
+    if position(ChunkType::IEND).is_none() {
+        report(end, ViolationKind::EndNotLast);
+    }
+    if first_idat.is_none() {
+        report(end, ViolationKind::MissingData);
+    }
+    let header = chunks
+        .first()
+        .filter(|chunk| *chunk.chunk_type() == ChunkType::IHDR)
+        .and_then(|chunk| ImageHeader::try_from(chunk.data()).ok());
+    if let Some(header) = header {
+        match (header.color_type, first_plte) {
+            (ColorType::Indexed, None) => report(end, ViolationKind::MissingPalette),
+            (ColorType::Grayscale | ColorType::GrayscaleAlpha, Some(index)) => {
+                report(index, ViolationKind::UnexpectedPalette)
+            }
+            _ => {}
+        }
+    }
+    violations.sort_by_key(|violation| violation.index);
+    violations
+}
+
+/// Where a chunk of this type can be inserted without breaking the ordering
+/// rules: IHDR first, IEND last, IDAT after the last IDAT, PLTE and the
+/// chunks that describe color before the image data and the palette
+/// dependent ones, the other chunks that must precede IDAT just before it,
+/// and everything else just before IEND
+pub fn default_position(chunks: &[Chunk], chunk_type: &ChunkType) -> usize {
+    let first = |found: &dyn Fn(&ChunkType) -> bool| {
+        chunks.iter().position(|chunk| found(chunk.chunk_type()))
+    };
+    let end = first(&|other| *other == ChunkType::IEND).unwrap_or(chunks.len());
+    let must = |constraint| registry::must(chunk_type, constraint);
+    match *chunk_type {
+        _ if must(Constraint::First) => 0,
+        _ if must(Constraint::Last) => chunks.len(),
+        _ if must(Constraint::Consecutive) => chunks
+            .iter()
+            .rposition(|chunk| chunk.chunk_type() == chunk_type)
+            .map_or(end, |last| last + 1),
+        ChunkType::PLTE => first(&|other| {
+            *other == ChunkType::IDAT || registry::must(other, Constraint::AfterPalette)
+        })
+        .unwrap_or(end),
+        _ if must(Constraint::BeforePalette) => {
+            first(&|other| *other == ChunkType::PLTE || *other == ChunkType::IDAT).unwrap_or(end)
+        }
+        _ if must(Constraint::BeforeImageData) => {
+            first(&|other| *other == ChunkType::IDAT).unwrap_or(end)
+        }
+        _ => end,
+    }
+}
+
+/// Pull the human readable reason out of a header error
+fn header_reason(err: PngError) -> String {
+    match err {
+        PngError::InvalidHeader { reason, .. } => reason,
+        other => other.to_string(),
+    }
+}
@@ -0,0 +1,226 @@
+use super::*;
+use crate::test_utils::{self, header};
+use std::str::FromStr;
+
+fn header_chunk(color_type: ColorType) -> Chunk {
+    header(1, 1, 8, color_type).to_chunk()
+}
+
+fn chunk(chunk_type: &str) -> Chunk {
+    test_utils::chunk(chunk_type, &[0; 3])
+}
+
+fn kinds(chunks: &[Chunk]) -> Vec<(usize, ViolationKind)> {
+    validate(chunks)
+        .into_iter()
+        .map(|violation| (violation.index, violation.kind))
+        .collect()
+}
+
+#[test]
+fn test_valid_chunks() {
+    let chunks = vec![
+        header_chunk(ColorType::Indexed),
+        chunk("gAMA"),
+        chunk("PLTE"),
+        chunk("tRNS"),
+        chunk("IDAT"),
+        chunk("IDAT"),
+        chunk("tEXt"),
+        chunk("IEND"),
+    ];
+    assert_eq!(validate(&chunks), []);
+}
+
+#[test]
+fn test_empty() {
+    assert_eq!(
+        kinds(&[]),
+        [
+            (0, ViolationKind::HeaderNotFirst),
+            (0, ViolationKind::EndNotLast),
+            (0, ViolationKind::MissingData),
+        ]
+    );
+}
+
+#[test]
+fn test_header_and_end_position() {
+    let chunks = vec![
+        chunk("IDAT"),
+        header_chunk(ColorType::Rgb),
+        chunk("IEND"),
+        chunk("tEXt"),
+    ];
+    assert_eq!(
+        kinds(&chunks),
+        [
+            (0, ViolationKind::HeaderNotFirst),
+            (1, ViolationKind::HeaderNotFirst),
+            (2, ViolationKind::EndNotLast),
+        ]
+    );
+}
+
+#[test]
+fn test_data_not_consecutive() {
+    let chunks = vec![
+        header_chunk(ColorType::Rgb),
+        chunk("IDAT"),
+        chunk("tEXt"),
+        chunk("IDAT"),
+        chunk("IEND"),
+    ];
+    assert_eq!(kinds(&chunks), [(3, ViolationKind::DataNotConsecutive)]);
+}
+
+#[test]
+fn test_ordering() {
+    let chunks = vec![
+        header_chunk(ColorType::Indexed),
+        chunk("bKGD"),
+        chunk("PLTE"),
+        chunk("sRGB"),
+        chunk("IDAT"),
+        chunk("pHYs"),
+        chunk("IEND"),
+    ];
+    assert_eq!(
+        kinds(&chunks),
+        [
+            (1, ViolationKind::MustFollow("bKGD".to_string(), "PLTE")),
+            (3, ViolationKind::MustPrecede("sRGB".to_string(), "PLTE")),
+            (5, ViolationKind::MustPrecede("pHYs".to_string(), "IDAT")),
+        ]
+    );
+}
+
+#[test]
+fn test_third_edition_ordering() {
+    let chunks = vec![
+        header_chunk(ColorType::Rgb),
+        chunk("fdAT"),
+        chunk("cLLI"),
+        chunk("IDAT"),
+        chunk("mDCV"),
+        chunk("IEND"),
+    ];
+    assert_eq!(
+        kinds(&chunks),
+        [
+            (1, ViolationKind::MustFollow("fdAT".to_string(), "IDAT")),
+            (4, ViolationKind::MustPrecede("mDCV".to_string(), "IDAT")),
+        ]
+    );
+}
+
+#[test]
+fn test_duplicates() {
+    let chunks = vec![
+        header_chunk(ColorType::Rgb),
+        chunk("gAMA"),
+        chunk("gAMA"),
+        chunk("IDAT"),
+        chunk("tEXt"),
+        chunk("tEXt"),
+        chunk("IEND"),
+    ];
+    assert_eq!(
+        kinds(&chunks),
+        [(2, ViolationKind::Duplicate("gAMA".to_string()))]
+    );
+}
+
+#[test]
+fn test_palette_rules() {
+    let chunks = vec![
+        header_chunk(ColorType::Indexed),
+        chunk("IDAT"),
+        chunk("IEND"),
+    ];
+    assert_eq!(kinds(&chunks), [(3, ViolationKind::MissingPalette)]);
+
+    let chunks = vec![
+        header_chunk(ColorType::Grayscale),
+        chunk("PLTE"),
+        chunk("IDAT"),
+        chunk("IEND"),
+    ];
+    assert_eq!(kinds(&chunks), [(1, ViolationKind::UnexpectedPalette)]);
+}
+
+#[test]
+fn test_reserved_bit_and_offsets() {
+    let chunks = vec![
+        header_chunk(ColorType::Rgb),
+        chunk("IDAT"),
+        chunk("rust"),
+        chunk("IEND"),
+    ];
+    let violations = validate(&chunks);
+    assert_eq!(violations.len(), 1);
+    assert_eq!(
+        violations[0].kind,
+        ViolationKind::ReservedBit("rust".into())
+    );
+    assert_eq!(violations[0].offset, 8 + 25 + 15);
+}
+
+#[test]
+fn test_invalid_header() {
+    let chunks = vec![chunk("IHDR"), chunk("IDAT"), chunk("IEND")];
+    assert!(matches!(
+        kinds(&chunks)[..],
+        [(0, ViolationKind::InvalidHeader(_))]
+    ));
+}
+
+#[test]
+fn test_default_position() {
+    let chunks = vec![
+        header_chunk(ColorType::Indexed),
+        chunk("sRGB"),
+        chunk("PLTE"),
+        chunk("tRNS"),
+        chunk("IDAT"),
+        chunk("IDAT"),
+        chunk("tEXt"),
+        chunk("IEND"),
+    ];
+    let position =
+        |chunk_type: &str| default_position(&chunks, &ChunkType::from_str(chunk_type).unwrap());
+    assert_eq!(position("IHDR"), 0);
+    assert_eq!(position("gAMA"), 2);
+    assert_eq!(position("PLTE"), 3);
+    assert_eq!(position("bKGD"), 4);
+    assert_eq!(position("pHYs"), 4);
+    assert_eq!(position("IDAT"), 6);
+    assert_eq!(position("tIME"), 7);
+    assert_eq!(position("ruSt"), 7);
+    assert_eq!(position("IEND"), 8);
+}
+
+#[test]
+fn test_default_position_keeps_chunks_valid() {
+    let mut chunks = vec![
+        header_chunk(ColorType::Indexed),
+        chunk("IDAT"),
+        chunk("IEND"),
+    ];
+    for chunk_type in ["tEXt", "tRNS", "PLTE", "gAMA", "pHYs", "IDAT", "ruSt"] {
+        let chunk = chunk(chunk_type);
+        let index = default_position(&chunks, chunk.chunk_type());
+        chunks.insert(index, chunk);
+    }
+    assert_eq!(kinds(&chunks), []);
+}
+
+#[test]
+fn test_default_position_without_end() {
+    let chunks = vec![header_chunk(ColorType::Rgb)];
+    let position =
+        |chunk_type: &str| default_position(&chunks, &ChunkType::from_str(chunk_type).unwrap());
+    assert_eq!(position("gAMA"), 1);
+    assert_eq!(position("IDAT"), 1);
+    assert_eq!(position("tEXt"), 1);
+}
@@ -0,0 +1,110 @@
+//! Streaming output of PNG files.
+//!
+//! A [`PngWriter`] writes the PNG signature as soon as it is created and then
+//! each chunk as it is handed over, so an edited file can go straight to a
+//! file or socket without first being assembled in memory.
+
+#[cfg(test)]
+mod unit_tests;
+
+use std::io::Write;
+
+use crate::{chunk::Chunk, chunk_type::ChunkType, error::PngError, png::Png};
+
+/// Writes a PNG signature followed by chunks to an underlying writer.
+///
+/// The IEND chunk marks the end of a PNG, so it may only be written once and
+/// no chunks may follow it. Call [`PngWriter::finish`] when done to add the
+/// IEND chunk if it hasn't been written yet.
+pub struct PngWriter<W: Write> {
+    inner: W,
+    offset: u64,
+    index: usize,
+    iend_written: bool,
+    raw_written: bool,
+}
+
+impl<W: Write> PngWriter<W> {
+    /// Write the PNG signature and get ready to write chunks
+    pub fn new(mut inner: W) -> Result<Self, PngError> {
+        inner
+            .write_all(&Png::STANDARD_HEADER)
+            .map_err(|source| PngError::Io {
+                source,
+                offset: 0,
+                index: 0,
+            })?;
+        Ok(PngWriter {
+            inner,
+            offset: Png::STANDARD_HEADER.len() as u64,
+            index: 0,
+            iend_written: false,
+            raw_written: false,
+        })
+    }
+    /// Write a single chunk. Fails if IEND has already been written.
+    pub fn write_chunk(&mut self, chunk: &Chunk) -> Result<(), PngError> {
+        if self.iend_written || self.raw_written {
+            return Err(PngError::ChunkAfterEnd {
+                chunk_type: chunk.chunk_type().to_string(),
+                offset: self.offset,
+                index: self.index,
+            });
+        }
+        if chunk.length() > Chunk::MAX_LENGTH {
+            return Err(PngError::LengthOverflow {
+                length: chunk.length().into(),
+                offset: self.offset,
+                index: self.index,
+            });
+        }
+        chunk
+            .write_to(&mut self.inner)
+            .map_err(|source| PngError::Io {
+                source,
+                offset: self.offset,
+                index: self.index,
+            })?;
+        self.offset += u64::from(chunk.length()) + 12;
+        self.index += 1;
+        self.iend_written = *chunk.chunk_type() == ChunkType::IEND;
+        Ok(())
+    }
+    /// Write bytes that aren't a chunk as they are, such as data found after
+    /// IEND. No chunks can be written afterwards, and [`PngWriter::finish`]
+    /// won't add an IEND chunk.
+    pub fn write_raw(&mut self, bytes: &[u8]) -> Result<(), PngError> {
+        self.inner.write_all(bytes).map_err(|source| PngError::Io {
+            source,
+            offset: self.offset,
+            index: self.index,
+        })?;
+        self.offset += bytes.len() as u64;
+        self.raw_written = true;
+        Ok(())
+    }
+    /// Write every chunk of a png, followed by its trailing bytes if it has
+    /// any
+    pub fn write_png(&mut self, png: &Png) -> Result<(), PngError> {
+        png.chunks()
+            .iter()
+            .try_for_each(|chunk| self.write_chunk(chunk))?;
+        if !png.trailing_bytes().is_empty() {
+            self.write_raw(png.trailing_bytes())?;
+        }
+        Ok(())
+    }
+    /// Write the IEND chunk if it hasn't been written yet, flush, and give
+    /// back the underlying writer
+    pub fn finish(mut self) -> Result<W, PngError> {
+        if !self.iend_written && !self.raw_written {
+            self.write_chunk(&Chunk::new(ChunkType::IEND, Vec::new()))?;
+        }
+        self.inner.flush().map_err(|source| PngError::Io {
+            source,
+            offset: self.offset,
+            index: self.index,
+        })?;
+        Ok(self.inner)
+    }
+}
@@ -0,0 +1,65 @@
+use super::*;
+use crate::reader::PngReader;
+use crate::test_utils;
+
+#[test]
+fn test_writes_signature() {
+    let bytes = PngWriter::new(Vec::new()).unwrap().inner;
+    assert_eq!(bytes, Png::STANDARD_HEADER);
+}
+
+#[test]
+fn test_finish_adds_iend() {
+    let mut writer = PngWriter::new(Vec::new()).unwrap();
+    writer
+        .write_chunk(&test_utils::chunk("FrSt", b"I am the first chunk"))
+        .unwrap();
+    let bytes = writer.finish().unwrap();
+    let types: Vec<String> = PngReader::new(bytes.as_slice())
+        .unwrap()
+        .map(|chunk| chunk.unwrap().chunk_type().to_string())
+        .collect();
+    assert_eq!(types, ["FrSt", "IEND"]);
+}
+
+#[test]
+fn test_iend_written_once() {
+    let png = Png::from_chunks(vec![
+        test_utils::chunk("FrSt", b"I am the first chunk"),
+        test_utils::chunk("IEND", b""),
+    ]);
+    let mut writer = PngWriter::new(Vec::new()).unwrap();
+    writer.write_png(&png).unwrap();
+    let bytes = writer.finish().unwrap();
+    assert_eq!(bytes, png.as_bytes());
+}
+
+#[test]
+fn test_nothing_after_iend() {
+    let mut writer = PngWriter::new(Vec::new()).unwrap();
+    writer.write_chunk(&test_utils::chunk("IEND", b"")).unwrap();
+    assert!(writer
+        .write_chunk(&test_utils::chunk("LASt", b"late"))
+        .is_err());
+    assert!(writer.write_chunk(&test_utils::chunk("IEND", b"")).is_err());
+}
+
+#[test]
+fn test_chunk_write_to_matches_as_bytes() {
+    let chunk = test_utils::chunk("RuSt", b"This is where your secret message will be!");
+    let mut bytes = Vec::new();
+    chunk.write_to(&mut bytes).unwrap();
+    assert_eq!(bytes, chunk.as_bytes());
+}
+
+#[test]
+fn test_trailing_bytes() {
+    let mut png = Png::from_chunks(vec![test_utils::chunk("FrSt", b"I am the first chunk")]);
+    png.set_trailing_bytes(b"not a chunk".to_vec());
+    let mut writer = PngWriter::new(Vec::new()).unwrap();
+    writer.write_png(&png).unwrap();
+    assert!(writer
+        .write_chunk(&test_utils::chunk("LASt", b"late"))
+        .is_err());
+    assert_eq!(writer.finish().unwrap(), png.as_bytes());
+}
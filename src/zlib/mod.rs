@@ -0,0 +1,116 @@
+//! zlib compression, as used by zTXt, iTXt and IDAT chunks.
+//!
+//! Inflating always takes a limit on the output size, since a few bytes of
+//! crafted input can expand to gigabytes.
+
+#[cfg(test)]
+mod unit_tests;
+
+use std::{
+    fmt::Display,
+    io::{Read, Write},
+};
+
+use flate2::{read::ZlibDecoder, write::ZlibEncoder, Compression};
+use miniz_oxide::deflate::core::{
+    compress_to_output, create_comp_flags_from_zip_params, CompressionStrategy, CompressorOxide,
+    TDEFLFlush, TDEFLStatus,
+};
+
+use crate::error::PngError;
+
+/// Compress with the given level, from 0 (store) to 9 (smallest). Callers
+/// check the level; it is passed on as is.
+pub fn deflate(bytes: &[u8], level: u32) -> Vec<u8> {
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::new(level));
+    encoder
+        .write_all(bytes)
+        .and_then(|_| encoder.finish())
+        .expect("writing to a vec can't fail")
+}
+
+/// How the compressor looks for repeated data. Which one gives the
+/// smallest output depends on the data.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Strategy {
+    /// Look for matches of any length
+    Default,
+    /// Only use matches of at least 5 bytes, which tends to suit filtered
+    /// image data
+    Filtered,
+    /// Don't look for matches, only Huffman code the bytes
+    HuffmanOnly,
+    /// Only look for runs of the same byte
+    Rle,
+    /// Only use the fixed Huffman codes from the deflate spec
+    Fixed,
+}
+
+impl Strategy {
+    pub const ALL: [Strategy; 5] = [
+        Strategy::Default,
+        Strategy::Filtered,
+        Strategy::HuffmanOnly,
+        Strategy::Rle,
+        Strategy::Fixed,
+    ];
+}
+
+impl Display for Strategy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Strategy::Default => "default",
+            Strategy::Filtered => "filtered",
+            Strategy::HuffmanOnly => "huffman-only",
+            Strategy::Rle => "rle",
+            Strategy::Fixed => "fixed",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// Compress with the given level, from 0 (store) to 9 (smallest), and
+/// strategy. Callers check the level; it is passed on as is.
+pub fn deflate_with_strategy(bytes: &[u8], level: u32, strategy: Strategy) -> Vec<u8> {
+    let strategy = match strategy {
+        Strategy::Default => CompressionStrategy::Default,
+        Strategy::Filtered => CompressionStrategy::Filtered,
+        Strategy::HuffmanOnly => CompressionStrategy::HuffmanOnly,
+        Strategy::Rle => CompressionStrategy::RLE,
+        Strategy::Fixed => CompressionStrategy::Fixed,
+    };
+    // positive window bits ask for a zlib header and trailer
+    let flags = create_comp_flags_from_zip_params(level as i32, 15, strategy as i32);
+    let mut compressor = CompressorOxide::new(flags);
+    let mut compressed = Vec::new();
+    let (status, _) = compress_to_output(&mut compressor, bytes, TDEFLFlush::Finish, |out| {
+        compressed.extend_from_slice(out);
+        true
+    });
+    assert_eq!(status, TDEFLStatus::Done, "writing to a vec can't fail");
+    compressed
+}
+
+/// Decompress a zlib stream, failing if the output would be longer than
+/// `limit` bytes
+pub fn inflate(bytes: &[u8], limit: u64) -> Result<Vec<u8>, PngError> {
+    let mut inflated = Vec::new();
+    ZlibDecoder::new(bytes)
+        .take(limit.saturating_add(1))
+        .read_to_end(&mut inflated)
+        .map_err(|err| invalid(format!("failed to decompress: {err}")))?;
+    if inflated.len() as u64 > limit {
+        return Err(invalid(format!(
+            "decompressed data is longer than the limit of {limit} bytes"
+        )));
+    }
+    Ok(inflated)
+}
+
+fn invalid(reason: String) -> PngError {
+    PngError::Decompression {
+        reason,
+        offset: 0,
+        index: 0,
+    }
+}
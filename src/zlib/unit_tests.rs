@@ -0,0 +1,50 @@
+use super::*;
+
+#[test]
+fn test_round_trip() {
+    let bytes = b"a message that repeats, a message that repeats".repeat(10);
+    for level in [0, 6, 9] {
+        let compressed = deflate(&bytes, level);
+        assert_eq!(
+            inflate(&compressed, 10_000).unwrap(),
+            bytes,
+            "level {level}"
+        );
+    }
+    assert!(deflate(&bytes, 9).len() < bytes.len() / 4);
+}
+
+#[test]
+fn test_limit() {
+    let compressed = deflate(&[0; 1000], 6);
+    assert_eq!(inflate(&compressed, 1000).unwrap().len(), 1000);
+    assert!(matches!(
+        inflate(&compressed, 999),
+        Err(PngError::Decompression { .. })
+    ));
+}
+
+#[test]
+fn test_not_zlib() {
+    assert!(inflate(b"not zlib at all", 1000).is_err());
+    let compressed = deflate(b"truncated stream", 6);
+    assert!(inflate(&compressed[..compressed.len() - 4], 1000).is_err());
+}
+
+#[test]
+fn test_strategies() {
+    let bytes = [b"aaaaaaaaaaaaaaaa".repeat(20), b"abcabcabc".repeat(20)].concat();
+    for strategy in Strategy::ALL {
+        for level in [0, 1, 9] {
+            let compressed = deflate_with_strategy(&bytes, level, strategy);
+            assert_eq!(
+                inflate(&compressed, 10_000).unwrap(),
+                bytes,
+                "{strategy} at level {level}"
+            );
+        }
+    }
+    let default = deflate_with_strategy(&bytes, 9, Strategy::Default);
+    let huffman = deflate_with_strategy(&bytes, 9, Strategy::HuffmanOnly);
+    assert!(default.len() < huffman.len());
+}